@@ -7,6 +7,7 @@ use odra::{
     Address,
 };
 pub mod message_transmitter;
+pub mod security;
 pub mod stablecoin;
 mod tests;
 pub mod token_messenger_minter;
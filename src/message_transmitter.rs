@@ -1,44 +1,69 @@
-use events::{MessageReceived, MessageSent};
+use events::{
+    AttesterRotationCanceled, AttesterRotationFinalized, AttesterRotationScheduled,
+    ExpiringMessagesSupportChanged, MessageReceiveFailed, MessageReceived, MessageRecipientChanged,
+    MessageSent, RemoteDomainAdded, RemoteDomainRemoved, StorageMigrated,
+};
 use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
 use odra::{
     casper_types::{
-        bytesrepr::{Bytes, ToBytes},
-        U256,
+        bytesrepr::{Bytes, FromBytes, ToBytes},
+        PublicKey, Signature as CasperSignature, ED25519_TAG, U256,
     },
     prelude::*,
-    Address, SubModule, UnwrapOrRevert, Var,
+    Address, OdraError, SubModule, UnwrapOrRevert, Var,
 };
 use sha3::{Digest, Keccak256};
-use storage::{Attesters, UsedNonces};
+use storage::{Attesters, MessageRoutes, Nonces};
 
 use crate::generic_address_to_contract_address;
 use crate::GenericAddress;
 use crate::{generic_address, EthAddress};
 
+pub mod attestation;
 pub mod errors;
 pub mod events;
 pub mod message;
+pub mod relayer_mock;
 pub mod storage;
+pub mod utils;
+use attestation::{
+    parse_attestation, AttesterKeyKind, AttesterSignature, ED25519_PUBLIC_KEY_LENGTH,
+    ED25519_SIGNATURE_LENGTH, SIGNATURE_LENGTH,
+};
 use message::Message;
+use utils::{AttesterRotation, ReceiveMode};
 
+use crate::security::errors::Error as SecurityError;
+use crate::security::timelock::operation_id;
+use crate::security::{Ownable2Step, Pausable, Timelock};
 use crate::token_messenger_minter::TokenMessengerMinterContractRef;
 use errors::Error;
 
-const SIGNATURE_LENGTH: usize = 65;
+/// Version of the storage layout written by this build of the contract.
+pub const STORAGE_VERSION: u32 = 4;
 
 #[odra::module]
 pub struct MessageTransmitter {
     local_domain: Var<u32>,
     version: Var<u32>,
-    paused: Var<bool>,
+    // superseded by `pausable` in storage version 2
+    legacy_paused: Var<bool>,
     max_message_body_size: Var<U256>,
     // mapping of source domain : nonce
     next_available_nonce: Var<u64>,
-    used_nonces: SubModule<UsedNonces>,
+    nonces: SubModule<Nonces>,
     attesters: SubModule<Attesters>,
     signature_threshold: Var<u32>,
-    owner: Var<Address>,
-    pending_owner: Var<Option<Address>>,
+    // superseded by `ownable` in storage version 2
+    legacy_owner: Var<Address>,
+    legacy_pending_owner: Var<Option<Address>>,
+    storage_version: Var<u32>,
+    ownable: SubModule<Ownable2Step>,
+    pausable: SubModule<Pausable>,
+    // remote domains messages can be sent to and local contracts they can be delivered to
+    routes: SubModule<MessageRoutes>,
+    // delay of sensitive owner actions and the guardian that can cancel them
+    timelock: SubModule<Timelock>,
 }
 
 #[odra::module]
@@ -52,15 +77,18 @@ impl MessageTransmitter {
         next_available_nonce: u64,
         signature_threshold: u32,
         owner: Address,
+        pauser_list: Vec<Address>,
     ) {
         self.local_domain.set(local_domain);
         self.version.set(version);
-        self.paused.set(false);
         self.max_message_body_size.set(max_message_body_size);
         self.signature_threshold.set(signature_threshold);
         self.next_available_nonce.set(next_available_nonce);
-        self.owner.set(owner);
-        self.pending_owner.set(None);
+        self.ownable.init(owner);
+        for pauser in pauser_list {
+            self.pausable.set_pauser(&pauser, true);
+        }
+        self.storage_version.set(STORAGE_VERSION);
     }
     pub fn send_message(
         &mut self,
@@ -81,6 +109,7 @@ impl MessageTransmitter {
             message_sender,
             nonce,
             message_body,
+            0,
         );
         nonce
     }
@@ -103,26 +132,69 @@ impl MessageTransmitter {
             message_sender,
             nonce,
             message_body,
+            0,
+        );
+        nonce
+    }
+    pub fn send_message_with_expiration(
+        &mut self,
+        destination_domain: u32,
+        recipient: GenericAddress,
+        message_body: Bytes,
+        destination_caller: GenericAddress,
+        expiration: u64,
+    ) -> u64 {
+        self.require_not_paused();
+        // an expiration that has already passed would produce an undeliverable message
+        if expiration <= self.env().get_block_time() {
+            self.env().revert(Error::InvalidExpiration)
+        }
+        // receivers that do not know the layout would misread the expiration as the body
+        if !self.routes.supports_expiring_messages(destination_domain) {
+            self.env().revert(Error::ExpiringMessagesNotSupported)
+        }
+        let nonce: u64 = self.next_available_nonce.get().unwrap();
+        self.next_available_nonce
+            .set(self.next_available_nonce.get().unwrap() + 1);
+        let message_sender: GenericAddress = generic_address(self.env().caller());
+        self._send_message(
+            destination_domain,
+            recipient,
+            destination_caller,
+            message_sender,
+            nonce,
+            message_body,
+            expiration,
         );
         nonce
     }
     pub fn replace_message(
-        &self,
+        &mut self,
         original_message: Bytes,
         original_attestation: Bytes,
         new_message_body: Bytes,
         new_destination_caller: GenericAddress,
     ) {
-        let original_msg: Message = Message::new(self.version.get().unwrap(), &original_message);
+        let original_msg: Message =
+            Message::try_new(self.version.get().unwrap(), &original_message)
+                .unwrap_or_revert_with(&self.env(), Error::InvalidMessage);
         let message_hasher = &original_msg.hash();
         // verify original attestation
         self.verify_attestation_signatures(message_hasher, &original_attestation);
+        // the original message must be the latest one sent by this transmitter under its nonce
+        if self.get_sent_message_hash(original_msg.nonce()) != Some(*message_hasher) {
+            self.env().revert(Error::UnknownOriginalMessage)
+        }
         let sender = original_msg.sender();
         // Message must be replaced by the MessengerMinter that submitted the original message.
-        assert_eq!(generic_address(self.env().caller()), sender);
+        if generic_address(self.env().caller()) != sender {
+            self.env().revert(Error::InvalidMessageSender)
+        }
         let destination_domain: u32 = original_msg.destination_domain();
         let recipient = original_msg.recipient();
         let nonce = original_msg.nonce();
+        // the replacement keeps the expiration of the original message
+        let expiration = original_msg.expiration();
 
         self._send_message(
             destination_domain,
@@ -131,110 +203,529 @@ impl MessageTransmitter {
             sender,
             nonce,
             new_message_body,
+            expiration,
         );
     }
     pub fn receive_message(&mut self, data: Bytes, attestation: Bytes) {
         self.require_not_paused();
-        let message: Message = Message::new(self.version.get().unwrap(), &data);
-        self.verify_attestation_signatures(&message.hash(), attestation.as_ref());
-        assert_eq!(message.version(), self.version.get().unwrap());
-        let destination_caller: [u8; 32] = message.destination_caller();
-        if destination_caller != [0u8; 32]
-            && destination_caller != generic_address(self.env().self_address())
-        {
-            self.env().revert(Error::InvalidMessageRecipient)
+        if let Err(error) = self._receive_message(&data, &attestation) {
+            self.env().revert(error)
         }
-        let token_messenger_minter_contract: TokenMessengerMinterContractRef =
-            TokenMessengerMinterContractRef::new(
-                self.env(),
-                generic_address_to_contract_address(message.recipient()),
-            );
-        let nonce: u64 = message.nonce();
-        let sender: [u8; 32] = message.sender();
-        let hashed_nonce: [u8; 32] = hash_nonce(nonce, sender);
-        let source_domain: u32 = message.source_domain();
-        let sender: [u8; 32] = message.sender();
-        let message_body: &[u8] = message.message_body();
-
-        assert!(!self.used_nonces.is_used_nonce(hashed_nonce));
-        self.used_nonces.use_nonce(hashed_nonce);
-
-        token_messenger_minter_contract.handle_receive_message(
-            source_domain,
-            sender,
-            Bytes::from(message_body.to_vec()),
-        );
-
-        self.env().emit_event(MessageReceived {
-            caller: generic_address(self.env().caller()),
-            source_domain,
-            nonce,
-            sender,
-            message_body: message_body.to_vec(),
-        })
+    }
+    // Receives the messages in the given order and returns how many were received.
+    // Failures detected by the transmitter, e.g. an invalid attestation or a used nonce,
+    // revert the batch in `AllOrNothing` mode and are skipped in `SkipFailed` mode.
+    // A failure inside the recipient contract always reverts the whole batch.
+    pub fn receive_messages(&mut self, messages: Vec<(Bytes, Bytes)>, mode: ReceiveMode) -> u32 {
+        self.require_not_paused();
+        if messages.is_empty() {
+            self.env().revert(Error::EmptyBatch)
+        }
+        let mut received: u32 = 0;
+        for (index, (data, attestation)) in messages.iter().enumerate() {
+            match self._receive_message(data, attestation) {
+                Ok(()) => received += 1,
+                Err(error) if matches!(mode, ReceiveMode::SkipFailed) => {
+                    self.env().emit_event(MessageReceiveFailed {
+                        index: index as u32,
+                        message_hash: keccak(data),
+                        error: u32::from(error.code()),
+                    })
+                }
+                Err(error) => self.env().revert(error),
+            }
+        }
+        received
     }
     pub fn set_max_message_body_size(&mut self, new_max_message_body_size: U256) {
-        self.require_owner();
+        if !self.owner_operation_ready(operation_id(
+            "set_max_message_body_size",
+            &new_max_message_body_size,
+        )) {
+            return;
+        }
         self.max_message_body_size.set(new_max_message_body_size);
     }
     pub fn set_signature_threshold(&mut self, new_signature_threshold: u32) {
-        self.require_owner();
+        if !self.owner_operation_ready(operation_id(
+            "set_signature_threshold",
+            &new_signature_threshold,
+        )) {
+            return;
+        }
         self.signature_threshold.set(new_signature_threshold);
     }
+    pub fn set_weight_threshold(&mut self, new_weight_threshold: u32) {
+        if !self.owner_operation_ready(operation_id("set_weight_threshold", &new_weight_threshold))
+        {
+            return;
+        }
+        self.attesters.set_weight_threshold(new_weight_threshold);
+    }
+    pub fn weight_threshold(&self) -> u32 {
+        self.attesters.weight_threshold()
+    }
     pub fn transfer_ownership(&mut self, new_pending_owner: Address) {
-        self.require_owner();
-        self.pending_owner.set(Some(new_pending_owner));
+        if !self.owner_operation_ready(operation_id("transfer_ownership", &new_pending_owner)) {
+            return;
+        }
+        self.ownable.transfer_ownership(&new_pending_owner);
     }
     pub fn accept_ownership(&mut self) {
-        let pending_owner = self.pending_owner.get().unwrap().unwrap();
-        if self.env().caller() != pending_owner {
-            self.env().revert(Error::InsufficientRights)
+        self.ownable.accept_ownership();
+    }
+    pub fn cancel_ownership_transfer(&mut self) {
+        self.ownable.cancel_ownership_transfer();
+    }
+    pub fn renounce_ownership(&mut self) {
+        if !self.owner_operation_ready(operation_id("renounce_ownership", &())) {
+            return;
         }
-        self.owner.set(pending_owner);
-        self.pending_owner.set(None);
+        self.ownable.renounce_ownership();
+    }
+    pub fn owner(&self) -> Option<Address> {
+        self.ownable.get_owner()
+    }
+    pub fn pending_owner(&self) -> Option<Address> {
+        self.ownable.get_pending_owner()
     }
     pub fn pause(&mut self) {
-        self.require_owner();
-        self.paused.set(true);
+        // the guardian can stop the contract without holding the pauser role
+        if !self.timelock.is_guardian(&self.env().caller()) {
+            self.require_pauser();
+        }
+        self.pausable.pause();
     }
     pub fn unpause(&mut self) {
-        self.require_owner();
-        self.paused.set(false);
+        // unlike pausing, resuming is left to the pausers so a guardian cannot undo a stop
+        self.require_pauser();
+        self.pausable.unpause();
+    }
+    pub fn is_paused(&self) -> bool {
+        self.pausable.is_paused()
+    }
+    pub fn set_destination_domain_paused(&mut self, domain: u32, paused: bool) {
+        self.require_pauser();
+        self.pausable.set_destination_domain_paused(domain, paused);
+    }
+    pub fn set_source_domain_paused(&mut self, domain: u32, paused: bool) {
+        self.require_pauser();
+        self.pausable.set_source_domain_paused(domain, paused);
+    }
+    pub fn is_destination_domain_paused(&self, domain: u32) -> bool {
+        self.pausable.is_destination_domain_paused(domain)
+    }
+    pub fn is_source_domain_paused(&self, domain: u32) -> bool {
+        self.pausable.is_source_domain_paused(domain)
+    }
+    pub fn set_pauser(&mut self, pauser: Address, enabled: bool) {
+        if !self.owner_operation_ready(operation_id("set_pauser", &(pauser, enabled))) {
+            return;
+        }
+        self.pausable.set_pauser(&pauser, enabled);
+    }
+    pub fn is_pauser(&self, account: Address) -> bool {
+        self.pausable.is_pauser(&account)
+    }
+    pub fn set_guardian(&mut self, guardian: Option<Address>) {
+        if !self.owner_operation_ready(operation_id("set_guardian", &guardian)) {
+            return;
+        }
+        self.timelock.set_guardian(guardian);
+    }
+    pub fn get_guardian(&self) -> Option<Address> {
+        self.timelock.get_guardian()
+    }
+    pub fn set_timelock_delay(&mut self, delay: u64) {
+        if !self.owner_operation_ready(operation_id("set_timelock_delay", &delay)) {
+            return;
+        }
+        self.timelock.set_delay(delay);
+    }
+    pub fn timelock_delay(&self) -> u64 {
+        self.timelock.get_delay()
+    }
+    pub fn get_operation_eta(&self, operation_id: [u8; 32]) -> Option<u64> {
+        self.timelock.get_operation_eta(operation_id)
+    }
+    pub fn cancel_operation(&mut self, operation_id: [u8; 32]) {
+        self.timelock.cancel(operation_id);
+    }
+    pub fn get_sent_message_hash(&self, nonce: u64) -> Option<[u8; 32]> {
+        self.nonces.get_sent_message_hash(nonce)
     }
     pub fn is_used_nonce(&self, nonce: u64, account: GenericAddress) -> bool {
         let nonce_hashed = hash_nonce(nonce, account);
-        self.used_nonces.is_used_nonce(nonce_hashed)
+        self.nonces.is_used_nonce(nonce_hashed)
+    }
+    pub fn add_remote_domain(&mut self, domain: u32) {
+        if !self.owner_operation_ready(operation_id("add_remote_domain", &domain)) {
+            return;
+        }
+        if domain == self.local_domain.get().unwrap() {
+            self.env().revert(Error::InvalidDestinationDomain)
+        }
+        if self.routes.is_remote_domain(domain) {
+            self.env().revert(Error::RemoteDomainAlreadyAdded)
+        }
+        self.routes.add_remote_domain(domain);
+        self.env().emit_event(RemoteDomainAdded { domain });
+    }
+    pub fn remove_remote_domain(&mut self, domain: u32) {
+        if !self.owner_operation_ready(operation_id("remove_remote_domain", &domain)) {
+            return;
+        }
+        if !self.routes.is_remote_domain(domain) {
+            self.env().revert(Error::UnknownRemoteDomain)
+        }
+        self.routes.remove_remote_domain(domain);
+        self.env().emit_event(RemoteDomainRemoved { domain });
+    }
+    pub fn is_remote_domain(&self, domain: u32) -> bool {
+        self.routes.is_remote_domain(domain)
+    }
+    pub fn set_message_recipient(&mut self, recipient: Address, enabled: bool) {
+        if !self.owner_operation_ready(operation_id("set_message_recipient", &(recipient, enabled)))
+        {
+            return;
+        }
+        self.require_contract_recipient(recipient);
+        self.routes
+            .set_message_recipient(generic_address(recipient), enabled);
+        self.env()
+            .emit_event(MessageRecipientChanged { recipient, enabled });
+    }
+    pub fn set_expiring_messages_supported(&mut self, domain: u32, supported: bool) {
+        if !self.owner_operation_ready(operation_id(
+            "set_expiring_messages_supported",
+            &(domain, supported),
+        )) {
+            return;
+        }
+        self.routes
+            .set_expiring_messages_supported(domain, supported);
+        self.env()
+            .emit_event(ExpiringMessagesSupportChanged { domain, supported });
+    }
+    pub fn supports_expiring_messages(&self, domain: u32) -> bool {
+        self.routes.supports_expiring_messages(domain)
+    }
+    pub fn is_message_recipient(&self, recipient: Address) -> bool {
+        self.routes.is_message_recipient(generic_address(recipient))
     }
     pub fn enable_attester(&mut self, new_attester: EthAddress) {
-        self.require_owner();
+        if !self.owner_operation_ready(operation_id("enable_attester", &new_attester)) {
+            return;
+        }
         self.attesters.enable_attester(new_attester);
     }
     pub fn disable_attester(&mut self, attester: EthAddress) {
-        self.require_owner();
+        if !self.owner_operation_ready(operation_id("disable_attester", &attester)) {
+            return;
+        }
         self.attesters.disable_attester(attester);
+        // an explicit disable wins over a scheduled rotation
+        self.attesters.remove_from_rotation(attester);
     }
-    fn require_not_paused(&self) {
-        if self.paused.get().unwrap() {
-            self.env().revert(Error::ContractIsPaused)
+    pub fn is_attester(&self, attester: EthAddress) -> bool {
+        self.attesters
+            .is_accepted_attester(attester, self.env().get_block_time())
+    }
+    pub fn enable_ed25519_attester(&mut self, public_key: [u8; 32]) {
+        if !self.owner_operation_ready(operation_id("enable_ed25519_attester", &public_key)) {
+            return;
+        }
+        self.attesters.enable_ed25519_attester(public_key);
+    }
+    pub fn disable_ed25519_attester(&mut self, public_key: [u8; 32]) {
+        if !self.owner_operation_ready(operation_id("disable_ed25519_attester", &public_key)) {
+            return;
+        }
+        self.attesters.disable_ed25519_attester(public_key);
+        // an explicit disable wins over a scheduled rotation
+        self.attesters.remove_ed25519_from_rotation(public_key);
+    }
+    pub fn is_ed25519_attester(&self, public_key: [u8; 32]) -> bool {
+        self.attesters
+            .is_accepted_ed25519_attester(public_key, self.env().get_block_time())
+    }
+    // Returns the secp256k1 attesters accepted at the current block time.
+    pub fn get_attesters(&self) -> Vec<EthAddress> {
+        self.attesters
+            .accepted_attesters(self.env().get_block_time())
+    }
+    // Returns the ed25519 attesters accepted at the current block time.
+    pub fn get_ed25519_attesters(&self) -> Vec<[u8; 32]> {
+        self.attesters
+            .accepted_ed25519_attesters(self.env().get_block_time())
+    }
+    // Returns the scheduled attester rotation until it is finalized.
+    pub fn get_attester_rotation(&self) -> Option<AttesterRotation> {
+        self.attesters.get_rotation()
+    }
+    pub fn schedule_attester_rotation(
+        &mut self,
+        enabled_attesters: Vec<EthAddress>,
+        disabled_attesters: Vec<EthAddress>,
+        enabled_ed25519_attesters: Vec<[u8; 32]>,
+        disabled_ed25519_attesters: Vec<[u8; 32]>,
+        activation_time: u64,
+        grace_period: u64,
+    ) {
+        if !self.owner_operation_ready(operation_id(
+            "schedule_attester_rotation",
+            &(
+                enabled_attesters.clone(),
+                disabled_attesters.clone(),
+                enabled_ed25519_attesters.clone(),
+                disabled_ed25519_attesters.clone(),
+                activation_time,
+                grace_period,
+            ),
+        )) {
+            return;
+        }
+        let block_time: u64 = self.env().get_block_time();
+        if activation_time < block_time {
+            self.env().revert(Error::InvalidActivationTime)
+        }
+        let new_rotation = AttesterRotation {
+            enabled_attesters,
+            disabled_attesters,
+            enabled_ed25519_attesters,
+            disabled_ed25519_attesters,
+            activation_time,
+            grace_period,
+        };
+        if new_rotation.has_overlap() {
+            self.env().revert(Error::OverlappingAttesterRotation)
+        }
+        // a rotation that has not taken effect yet is replaced, an active one has to settle first
+        if let Some(rotation) = self.attesters.get_rotation() {
+            if rotation.is_settled(block_time) {
+                self.finalize_rotation(rotation);
+            } else if rotation.is_active(block_time) {
+                self.env().revert(Error::AttesterRotationInProgress)
+            }
+        }
+        self.attesters.set_rotation(Some(new_rotation));
+        self.env().emit_event(AttesterRotationScheduled {
+            activation_time,
+            grace_period,
+        });
+    }
+    pub fn cancel_attester_rotation(&mut self) {
+        if !self.owner_operation_ready(operation_id("cancel_attester_rotation", &())) {
+            return;
+        }
+        let rotation: AttesterRotation = self
+            .attesters
+            .get_rotation()
+            .unwrap_or_revert_with(&self.env(), Error::NoAttesterRotation);
+        if rotation.is_active(self.env().get_block_time()) {
+            self.env().revert(Error::AttesterRotationInProgress)
+        }
+        self.attesters.set_rotation(None);
+        self.env().emit_event(AttesterRotationCanceled {
+            activation_time: rotation.activation_time,
+        });
+    }
+    // Applies a rotation whose grace period is over to the attesters, callable by anyone.
+    pub fn finalize_attester_rotation(&mut self) {
+        let rotation: AttesterRotation = self
+            .attesters
+            .get_rotation()
+            .unwrap_or_revert_with(&self.env(), Error::NoAttesterRotation);
+        if !rotation.is_settled(self.env().get_block_time()) {
+            self.env().revert(Error::AttesterRotationInProgress)
+        }
+        self.finalize_rotation(rotation);
+    }
+    pub fn set_attester_weight(&mut self, attester: EthAddress, weight: u32) {
+        if !self.owner_operation_ready(operation_id("set_attester_weight", &(attester, weight))) {
+            return;
+        }
+        self.attesters.set_attester_weight(attester, weight);
+    }
+    pub fn attester_weight(&self, attester: EthAddress) -> u32 {
+        self.attesters.attester_weight(attester)
+    }
+    pub fn set_ed25519_attester_weight(&mut self, public_key: [u8; 32], weight: u32) {
+        if !self.owner_operation_ready(operation_id(
+            "set_ed25519_attester_weight",
+            &(public_key, weight),
+        )) {
+            return;
+        }
+        self.attesters
+            .set_ed25519_attester_weight(public_key, weight);
+    }
+    pub fn ed25519_attester_weight(&self, public_key: [u8; 32]) -> u32 {
+        self.attesters.ed25519_attester_weight(public_key)
+    }
+    pub fn storage_version(&self) -> u32 {
+        self.storage_version.get_or_default()
+    }
+    // Migrates the storage of a previous version to the layout of the installed code,
+    // called by the owner once after a contract package upgrade. Messages could be sent to
+    // any domain and delivered to any recipient before storage version 3, `remote_domains`
+    // and `message_recipients` seed the allowlists that restrict them since. The enabled
+    // attesters are only listed since storage version 4, `attesters` and `ed25519_attesters`
+    // list the ones enabled before.
+    pub fn migrate(
+        &mut self,
+        remote_domains: Vec<u32>,
+        message_recipients: Vec<Address>,
+        attesters: Vec<EthAddress>,
+        ed25519_attesters: Vec<[u8; 32]>,
+    ) {
+        let from_version = self.storage_version();
+        // before storage version 2 the owner is only known to the legacy storage
+        if from_version < 2 {
+            if self.legacy_owner.get() != Some(self.env().caller()) {
+                self.env().revert(SecurityError::NotOwner)
+            }
+        } else {
+            self.require_owner();
         }
+        if from_version >= STORAGE_VERSION {
+            self.env().revert(Error::StorageUpToDate)
+        }
+        for version in from_version..STORAGE_VERSION {
+            self.migrate_from(
+                version,
+                &remote_domains,
+                &message_recipients,
+                &attesters,
+                &ed25519_attesters,
+            );
+        }
+        self.storage_version.set(STORAGE_VERSION);
+        self.env().emit_event(StorageMigrated {
+            from_version,
+            to_version: STORAGE_VERSION,
+        });
+    }
+    fn migrate_from(
+        &mut self,
+        version: u32,
+        remote_domains: &[u32],
+        message_recipients: &[Address],
+        attesters: &[EthAddress],
+        ed25519_attesters: &[[u8; 32]],
+    ) {
+        match version {
+            // unversioned installations already use the version 1 layout
+            0 => {}
+            // ownership and the pause flag moved into the shared submodules,
+            // a pending ownership transfer is dropped and has to be started again
+            1 => {
+                if let Some(owner) = self.legacy_owner.get() {
+                    self.ownable.init(owner);
+                    // the owner used to pause the contract
+                    self.pausable.set_pauser(&owner, true);
+                }
+                self.legacy_pending_owner.set(None);
+                if self.legacy_paused.get_or_default() {
+                    self.pausable.pause();
+                }
+            }
+            // messages are restricted to the allowlisted remote domains and local recipients
+            2 => {
+                for domain in remote_domains {
+                    if *domain == self.local_domain.get().unwrap() {
+                        self.env().revert(Error::InvalidDestinationDomain)
+                    }
+                    if !self.routes.is_remote_domain(*domain) {
+                        self.routes.add_remote_domain(*domain);
+                        self.env().emit_event(RemoteDomainAdded { domain: *domain });
+                    }
+                }
+                for recipient in message_recipients {
+                    self.require_contract_recipient(*recipient);
+                    self.routes
+                        .set_message_recipient(generic_address(*recipient), true);
+                    self.env().emit_event(MessageRecipientChanged {
+                        recipient: *recipient,
+                        enabled: true,
+                    });
+                }
+            }
+            // the enabled attesters are listed, the mapping of earlier versions is not iterable
+            3 => {
+                for attester in attesters {
+                    if !self.attesters.is_attester(*attester) {
+                        self.env().revert(Error::UnknownAttester)
+                    }
+                    self.attesters.enable_attester(*attester);
+                }
+                for public_key in ed25519_attesters {
+                    if !self.attesters.is_ed25519_attester(*public_key) {
+                        self.env().revert(Error::UnknownAttester)
+                    }
+                    self.attesters.enable_ed25519_attester(*public_key);
+                }
+            }
+            _ => self.env().revert(Error::UnknownStorageVersion),
+        }
+    }
+    fn require_not_paused(&self) {
+        self.pausable.require_not_paused();
     }
     fn require_owner(&self) {
-        if self.env().caller() != self.owner.get().unwrap() {
-            self.env().revert(Error::InsufficientRights)
+        self.ownable.assert_owner(&self.env().caller());
+    }
+    // messages are delivered by calling the recipient, which an account cannot handle
+    fn require_contract_recipient(&self, recipient: Address) {
+        if !recipient.is_contract() || recipient == self.env().self_address() {
+            self.env().revert(Error::InvalidMessageRecipient)
+        }
+    }
+    fn require_remote_domain(&self, destination_domain: u32) {
+        if destination_domain == self.local_domain.get().unwrap() {
+            self.env().revert(Error::InvalidDestinationDomain)
+        }
+        if !self.routes.is_remote_domain(destination_domain) {
+            self.env().revert(Error::UnknownRemoteDomain)
+        }
+    }
+    fn require_valid_message_body_size(&self, message_body_size: usize) {
+        if U256::from(message_body_size) > self.max_message_body_size.get().unwrap() {
+            self.env().revert(Error::MessageBodyTooLarge)
         }
     }
+    fn finalize_rotation(&mut self, rotation: AttesterRotation) {
+        self.attesters.apply_rotation();
+        self.env().emit_event(AttesterRotationFinalized {
+            activation_time: rotation.activation_time,
+        });
+    }
+    // Requires the owner and returns true once a sensitive action may be executed, actions
+    // are queued for the timelock delay first.
+    fn owner_operation_ready(&mut self, operation_id: Result<[u8; 32], SecurityError>) -> bool {
+        self.require_owner();
+        let operation_id: [u8; 32] = operation_id.unwrap_or_else(|error| self.env().revert(error));
+        self.timelock.schedule_or_execute(operation_id)
+    }
+    fn require_pauser(&self) {
+        self.pausable.assert_pauser(&self.env().caller());
+    }
+    #[allow(clippy::too_many_arguments)]
     fn _send_message(
-        &self,
+        &mut self,
         destination_domain: u32,
         recipient: GenericAddress,
         destination_caller: GenericAddress,
         sender: GenericAddress,
         nonce: u64,
         message_body: Bytes,
+        expiration: u64,
     ) {
+        self.require_remote_domain(destination_domain);
+        self.pausable
+            .require_destination_domain_not_paused(destination_domain);
         assert_ne!(recipient, [0u8; 32]);
-        // Validate message body length
-        assert!(U256::from(message_body.len()) <= self.max_message_body_size.get().unwrap());
+        self.require_valid_message_body_size(message_body.len());
         let message_body: &Vec<u8> = &Message::format_message(
             self.version.get().unwrap(),
             self.local_domain.get().unwrap(),
@@ -243,27 +734,163 @@ impl MessageTransmitter {
             &sender,
             &recipient,
             &destination_caller,
+            expiration,
             message_body.as_ref(),
         );
         let message: Message = Message::new(self.version.get().unwrap(), message_body);
+        self.nonces.set_sent_message_hash(nonce, message.hash());
         self.env().emit_event(MessageSent {
             message: message.data.to_vec(),
         });
     }
-    fn verify_attestation_signatures(&self, message_hash: &[u8; 32], attestation: &[u8]) {
-        if (65u32 * self.signature_threshold.get().unwrap_or_revert(&self.env())) as usize
-            != attestation.len()
+    // Validates and delivers a single message, returning the failures the transmitter
+    // can detect itself instead of reverting.
+    fn _receive_message(&mut self, data: &[u8], attestation: &[u8]) -> Result<(), OdraError> {
+        let message: Message =
+            Message::try_new(self.version.get().unwrap(), data).ok_or(Error::InvalidMessage)?;
+        if self
+            .pausable
+            .is_source_domain_paused(message.source_domain())
         {
-            self.env().revert(Error::InvalidAttestationLength)
+            return Err(SecurityError::SourceDomainPaused.into());
+        }
+        self.check_attestation_signatures(&message.hash(), attestation)?;
+        // a message attested for another chain must not be replayed here
+        if message.destination_domain() != self.local_domain.get().unwrap() {
+            return Err(Error::InvalidDestinationDomain.into());
+        }
+        if message.is_expired(self.env().get_block_time()) {
+            return Err(Error::MessageExpired.into());
         }
-        let mut last_attester: EthAddress = [0u8; 20];
-        for signature in attestation.to_vec().chunks(SIGNATURE_LENGTH) {
-            let pubkey_recovered: EthAddress =
-                recover_attester(message_hash, signature.try_into().unwrap());
-            assert!(pubkey_recovered > last_attester);
-            assert!(self.attesters.is_attester(pubkey_recovered));
-            last_attester = pubkey_recovered;
+        if U256::from(message.message_body().len()) > self.max_message_body_size.get().unwrap() {
+            return Err(Error::MessageBodyTooLarge.into());
         }
+        // The recipient is always called as a contract. The message only carries its hash,
+        // which cannot tell accounts and contracts apart, so only the contracts registered
+        // with `set_message_recipient` are called.
+        let recipient: GenericAddress = message.recipient();
+        if !self.routes.is_message_recipient(recipient) {
+            return Err(Error::InvalidMessageRecipient.into());
+        }
+        // a message restricted to a destination caller can only be delivered by that account
+        let destination_caller: [u8; 32] = message.destination_caller();
+        if destination_caller != [0u8; 32]
+            && destination_caller != generic_address(self.env().caller())
+        {
+            return Err(Error::InvalidDestinationCaller.into());
+        }
+        let token_messenger_minter_contract: TokenMessengerMinterContractRef =
+            TokenMessengerMinterContractRef::new(
+                self.env(),
+                generic_address_to_contract_address(recipient),
+            );
+        let nonce: u64 = message.nonce();
+        let sender: [u8; 32] = message.sender();
+        let hashed_nonce: [u8; 32] = hash_nonce(nonce, sender);
+        let source_domain: u32 = message.source_domain();
+        let message_body: &[u8] = message.message_body();
+
+        if self.nonces.is_used_nonce(hashed_nonce) {
+            return Err(Error::NonceAlreadyUsed.into());
+        }
+        self.nonces.use_nonce(hashed_nonce);
+
+        token_messenger_minter_contract.handle_receive_message(
+            source_domain,
+            sender,
+            Bytes::from(message_body.to_vec()),
+        );
+
+        self.env().emit_event(MessageReceived {
+            caller: generic_address(self.env().caller()),
+            source_domain,
+            nonce,
+            sender,
+            message_body: message_body.to_vec(),
+        });
+        Ok(())
+    }
+    fn verify_attestation_signatures(&self, message_hash: &[u8; 32], attestation: &[u8]) {
+        if let Err(error) = self.check_attestation_signatures(message_hash, attestation) {
+            self.env().revert(error)
+        }
+    }
+    fn check_attestation_signatures(
+        &self,
+        message_hash: &[u8; 32],
+        attestation: &[u8],
+    ) -> Result<(), Error> {
+        let signatures: Vec<AttesterSignature> = parse_attestation(
+            attestation,
+            self.signature_threshold.get().unwrap_or_revert(&self.env()) as usize,
+        )?;
+        let mut last_attester: Option<(AttesterKeyKind, [u8; 32])> = None;
+        let mut total_weight: u64 = 0;
+        let block_time: u64 = self.env().get_block_time();
+        for signature in signatures {
+            let attester: (AttesterKeyKind, [u8; 32]) = match signature {
+                AttesterSignature::Secp256k1(signature) => {
+                    let pubkey_recovered: EthAddress = recover_attester(message_hash, signature)?;
+                    if !self
+                        .attesters
+                        .is_accepted_attester(pubkey_recovered, block_time)
+                    {
+                        return Err(Error::InvalidAttesterSignature);
+                    }
+                    let mut key: [u8; 32] = [0u8; 32];
+                    key[12..].copy_from_slice(&pubkey_recovered);
+                    total_weight += self.attesters.attester_weight(pubkey_recovered) as u64;
+                    (AttesterKeyKind::Secp256k1Eth, key)
+                }
+                AttesterSignature::Ed25519 {
+                    public_key,
+                    signature,
+                } => {
+                    if !self
+                        .attesters
+                        .is_accepted_ed25519_attester(*public_key, block_time)
+                        || !self.verify_ed25519_signature(message_hash, public_key, signature)
+                    {
+                        return Err(Error::InvalidAttesterSignature);
+                    }
+                    total_weight += self.attesters.ed25519_attester_weight(*public_key) as u64;
+                    (AttesterKeyKind::Ed25519, *public_key)
+                }
+            };
+            // attesters have to sign in ascending order of key kind and key, which rules out
+            // duplicates
+            if Some(attester) <= last_attester {
+                return Err(Error::InvalidAttesterSignature);
+            }
+            last_attester = Some(attester);
+        }
+        if total_weight < self.weight_threshold() as u64 {
+            return Err(Error::InsufficientAttesterWeight);
+        }
+        Ok(())
+    }
+    fn verify_ed25519_signature(
+        &self,
+        message_hash: &[u8; 32],
+        public_key: &[u8; ED25519_PUBLIC_KEY_LENGTH],
+        signature: &[u8; ED25519_SIGNATURE_LENGTH],
+    ) -> bool {
+        // keys and signatures are serialized with the tag of their algorithm in front
+        let mut public_key_bytes: Vec<u8> = vec![ED25519_TAG];
+        public_key_bytes.extend_from_slice(public_key);
+        let mut signature_bytes: Vec<u8> = vec![ED25519_TAG];
+        signature_bytes.extend_from_slice(signature);
+        let (Ok((public_key, _)), Ok(_)) = (
+            PublicKey::from_bytes(&public_key_bytes),
+            CasperSignature::from_bytes(&signature_bytes),
+        ) else {
+            return false;
+        };
+        self.env().verify_signature(
+            &Bytes::from(message_hash.to_vec()),
+            &Bytes::from(signature_bytes),
+            &public_key,
+        )
     }
 }
 fn hash_nonce(nonce: u64, account: GenericAddress) -> [u8; 32] {
@@ -273,34 +900,41 @@ fn hash_nonce(nonce: u64, account: GenericAddress) -> [u8; 32] {
     hasher.finalize().as_slice().try_into().unwrap()
 }
 
-fn recover_attester(message_hash: &[u8; 32], signature: &[u8; SIGNATURE_LENGTH]) -> EthAddress {
+// Recovers the Ethereum address of the signer. Like OpenZeppelin's ECDSA, only accepts
+// signatures with a low s value and a recovery byte of 27 or 28, so a signature cannot be
+// altered into another valid signature of the same signer.
+fn recover_attester(
+    message_hash: &[u8; 32],
+    signature: &[u8; SIGNATURE_LENGTH],
+) -> Result<EthAddress, Error> {
     let recovery_byte = signature[SIGNATURE_LENGTH - 1];
-    assert!((27..=30).contains(&recovery_byte));
-    let recovery_id = RecoveryId::from_byte(signature[SIGNATURE_LENGTH - 1] - 27u8)
-        .expect("Failed to get recovery Id");
-    let signature: [u8; SIGNATURE_LENGTH - 1] = signature[0..SIGNATURE_LENGTH - 1]
-        .try_into()
-        .expect("Failed to read signatrue");
-    // todo: refactor using recover_from_prehash
-    let recovered_key = VerifyingKey::recover_from_prehash(
-        message_hash,
-        &Signature::from_slice(&signature).unwrap(),
-        recovery_id,
-    )
-    .expect("Failed to recover from prehash");
-    recover_ethereum_address(
-        recovered_key.to_encoded_point(false).as_ref()[1..]
-            .try_into()
-            .expect("Failed to fit pubkey into slice"),
-    )
+    if !(27..=28).contains(&recovery_byte) {
+        return Err(Error::InvalidSignatureRecoveryId);
+    }
+    let recovery_id =
+        RecoveryId::from_byte(recovery_byte - 27u8).ok_or(Error::InvalidSignatureRecoveryId)?;
+    let signature = Signature::from_slice(&signature[0..SIGNATURE_LENGTH - 1])
+        .map_err(|_| Error::InvalidSignature)?;
+    // normalizing succeeds only for a signature in the upper half of the curve order
+    if signature.normalize_s().is_some() {
+        return Err(Error::InvalidSignatureS);
+    }
+    let recovered_key = VerifyingKey::recover_from_prehash(message_hash, &signature, recovery_id)
+        .map_err(|_| Error::InvalidSignature)?;
+    // the uncompressed encoding is prefixed with a single tag byte
+    Ok(recover_ethereum_address(
+        &recovered_key.to_encoded_point(false).as_bytes()[1..],
+    ))
 }
-fn recover_ethereum_address(pubkey: [u8; 64]) -> EthAddress {
+fn keccak(data: &[u8]) -> [u8; 32] {
     let mut hasher = Keccak256::new();
-    hasher.update(pubkey);
-    let hash = hasher.finalize();
-    hash.as_slice()[12..]
-        .try_into()
-        .expect("Failed to fit pubkey into slice")
+    hasher.update(data);
+    hasher.finalize().as_slice().try_into().unwrap()
+}
+fn recover_ethereum_address(pubkey: &[u8]) -> EthAddress {
+    let mut address: EthAddress = [0u8; 20];
+    address.copy_from_slice(&keccak(pubkey)[12..]);
+    address
 }
 
 #[test]
@@ -317,12 +951,60 @@ fn test_recover_ethereum_key() {
         sk.sign_prehash_recoverable(&message_hash).unwrap();
     let mut signature_bytes = signature.to_bytes().to_vec();
     signature_bytes.push(recovery_id.to_byte() + 27u8);
-    let attester = recover_attester(&message_hash, &signature_bytes.try_into().unwrap());
+    let attester = recover_attester(&message_hash, &signature_bytes.try_into().unwrap()).ok();
     use alloy::primitives::Address;
     let expected_attester: [u8; 20] = Address::from_public_key(&public_key)
         .to_bytes()
         .unwrap()
         .try_into()
         .unwrap();
-    assert_eq!(attester, expected_attester);
+    assert_eq!(attester, Some(expected_attester));
+}
+
+#[test]
+fn test_recover_attester_rejects_malformed_signatures() {
+    use k256::ecdsa::SigningKey;
+    let message_hash: [u8; 32] = keccak(&[2; 32]);
+    let sk = SigningKey::from_slice(&[1; 32]).unwrap();
+    let (signature, recovery_id): (Signature, RecoveryId) =
+        sk.sign_prehash_recoverable(&message_hash).unwrap();
+    let with_recovery_byte = |signature: &Signature, recovery_byte: u8| -> [u8; SIGNATURE_LENGTH] {
+        let mut signature_bytes = signature.to_bytes().to_vec();
+        signature_bytes.push(recovery_byte);
+        signature_bytes.try_into().unwrap()
+    };
+    let recovery_error = |signature: [u8; SIGNATURE_LENGTH]| -> Option<OdraError> {
+        recover_attester(&message_hash, &signature)
+            .err()
+            .map(OdraError::from)
+    };
+    let valid = with_recovery_byte(&signature, recovery_id.to_byte() + 27);
+    assert_eq!(recovery_error(valid), None);
+
+    // recovery ids other than 0 and 1, with and without the 27 offset
+    for recovery_byte in [0, 1, 26, 29, 30, 255] {
+        assert_eq!(
+            recovery_error(with_recovery_byte(&signature, recovery_byte)),
+            Some(Error::InvalidSignatureRecoveryId.into())
+        );
+    }
+    // the high-s twin of a valid signature recovers the same key with the other parity
+    let high_s = Signature::from_scalars(signature.r(), -signature.s()).unwrap();
+    assert_eq!(
+        recovery_error(with_recovery_byte(
+            &high_s,
+            (recovery_id.to_byte() ^ 1) + 27
+        )),
+        Some(Error::InvalidSignatureS.into())
+    );
+    // r and s have to be non-zero scalars
+    let mut zero_r = valid;
+    zero_r[..32].copy_from_slice(&[0u8; 32]);
+    assert_eq!(recovery_error(zero_r), Some(Error::InvalidSignature.into()));
+    let mut overflowing_s = valid;
+    overflowing_s[32..64].copy_from_slice(&[0xffu8; 32]);
+    assert_eq!(
+        recovery_error(overflowing_s),
+        Some(Error::InvalidSignature.into())
+    );
 }
@@ -0,0 +1,89 @@
+use super::errors::Error;
+extern crate alloc;
+use alloc::vec::Vec;
+
+/// Length of a secp256k1 signature followed by its recovery byte.
+pub const SIGNATURE_LENGTH: usize = 65;
+pub const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
+pub const ED25519_SIGNATURE_LENGTH: usize = 64;
+/// First byte of an attestation in the versioned encoding.
+pub const ATTESTATION_VERSION: u8 = 1;
+
+/// Kind of the key an attester signs with, prefixed to every signature of a versioned attestation.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AttesterKeyKind {
+    /// secp256k1 key identified by its Ethereum address
+    Secp256k1Eth = 0,
+    /// Casper ed25519 public key
+    Ed25519 = 1,
+}
+
+impl TryFrom<u8> for AttesterKeyKind {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AttesterKeyKind::Secp256k1Eth),
+            1 => Ok(AttesterKeyKind::Ed25519),
+            _ => Err(Error::UnknownAttesterKeyKind),
+        }
+    }
+}
+
+pub enum AttesterSignature<'a> {
+    Secp256k1(&'a [u8; SIGNATURE_LENGTH]),
+    Ed25519 {
+        public_key: &'a [u8; ED25519_PUBLIC_KEY_LENGTH],
+        signature: &'a [u8; ED25519_SIGNATURE_LENGTH],
+    },
+}
+
+/// Splits an attestation into exactly `signature_count` signatures.
+///
+/// An attestation of `signature_count` concatenated secp256k1 signatures is read in
+/// Circle's format. Any other attestation must be versioned: `ATTESTATION_VERSION` followed
+/// by the signatures, each prefixed with its `AttesterKeyKind` byte. An ed25519 signature is
+/// preceded by the public key of its signer, as the key cannot be recovered from it.
+/// A versioned attestation is 1 + 66 * secp256k1 + 97 * ed25519 bytes long, so it can never
+/// be mistaken for an attestation in Circle's format.
+pub fn parse_attestation(
+    attestation: &[u8],
+    signature_count: usize,
+) -> Result<Vec<AttesterSignature<'_>>, Error> {
+    if attestation.len() == SIGNATURE_LENGTH * signature_count {
+        return Ok(attestation
+            .chunks(SIGNATURE_LENGTH)
+            .map(|signature| AttesterSignature::Secp256k1(signature.try_into().unwrap()))
+            .collect());
+    }
+    if attestation.first() != Some(&ATTESTATION_VERSION) {
+        return Err(Error::InvalidAttestationLength);
+    }
+    let mut signatures: Vec<AttesterSignature> = Vec::with_capacity(signature_count);
+    let mut data: &[u8] = &attestation[1..];
+    while let Some((kind, rest)) = data.split_first() {
+        let kind = AttesterKeyKind::try_from(*kind)?;
+        let length: usize = match kind {
+            AttesterKeyKind::Secp256k1Eth => SIGNATURE_LENGTH,
+            AttesterKeyKind::Ed25519 => ED25519_PUBLIC_KEY_LENGTH + ED25519_SIGNATURE_LENGTH,
+        };
+        if rest.len() < length {
+            return Err(Error::InvalidAttestationLength);
+        }
+        let (signature, rest) = rest.split_at(length);
+        signatures.push(match kind {
+            AttesterKeyKind::Secp256k1Eth => {
+                AttesterSignature::Secp256k1(signature.try_into().unwrap())
+            }
+            AttesterKeyKind::Ed25519 => AttesterSignature::Ed25519 {
+                public_key: signature[..ED25519_PUBLIC_KEY_LENGTH].try_into().unwrap(),
+                signature: signature[ED25519_PUBLIC_KEY_LENGTH..].try_into().unwrap(),
+            },
+        });
+        data = rest;
+    }
+    if signatures.len() != signature_count {
+        return Err(Error::InvalidAttestationLength);
+    }
+    Ok(signatures)
+}
@@ -6,4 +6,29 @@ pub enum Error {
     InvalidMessageRecipient = 50002,
     InvalidSignatureRecoveryId = 50003,
     InvalidAttestationLength = 50004,
+    StorageUpToDate = 50005,
+    UnknownStorageVersion = 50006,
+    InvalidDestinationDomain = 50007,
+    UnknownRemoteDomain = 50008,
+    RemoteDomainAlreadyAdded = 50009,
+    InvalidDestinationCaller = 50010,
+    MessageBodyTooLarge = 50011,
+    UnknownOriginalMessage = 50012,
+    InvalidMessage = 50013,
+    InvalidMessageSender = 50014,
+    NonceAlreadyUsed = 50015,
+    InvalidAttesterSignature = 50016,
+    EmptyBatch = 50017,
+    InvalidExpiration = 50018,
+    MessageExpired = 50019,
+    ExpiringMessagesNotSupported = 50020,
+    UnknownAttesterKeyKind = 50021,
+    InsufficientAttesterWeight = 50022,
+    InvalidActivationTime = 50023,
+    AttesterRotationInProgress = 50024,
+    NoAttesterRotation = 50025,
+    OverlappingAttesterRotation = 50026,
+    UnknownAttester = 50027,
+    InvalidSignatureS = 50028,
+    InvalidSignature = 50029,
 }
@@ -1,5 +1,5 @@
 use crate::GenericAddress;
-use odra::prelude::*;
+use odra::{prelude::*, Address};
 
 #[odra::event]
 pub struct MessageSent {
@@ -14,3 +14,56 @@ pub struct MessageReceived {
     pub sender: GenericAddress,
     pub message_body: Vec<u8>,
 }
+
+#[odra::event]
+pub struct StorageMigrated {
+    pub from_version: u32,
+    pub to_version: u32,
+}
+
+#[odra::event]
+pub struct RemoteDomainAdded {
+    pub domain: u32,
+}
+
+#[odra::event]
+pub struct RemoteDomainRemoved {
+    pub domain: u32,
+}
+
+#[odra::event]
+pub struct MessageRecipientChanged {
+    pub recipient: Address,
+    pub enabled: bool,
+}
+
+#[odra::event]
+pub struct ExpiringMessagesSupportChanged {
+    pub domain: u32,
+    pub supported: bool,
+}
+
+#[odra::event]
+pub struct MessageReceiveFailed {
+    // position of the message in the batch
+    pub index: u32,
+    // keccak hash of the message data
+    pub message_hash: [u8; 32],
+    pub error: u32,
+}
+
+#[odra::event]
+pub struct AttesterRotationScheduled {
+    pub activation_time: u64,
+    pub grace_period: u64,
+}
+
+#[odra::event]
+pub struct AttesterRotationCanceled {
+    pub activation_time: u64,
+}
+
+#[odra::event]
+pub struct AttesterRotationFinalized {
+    pub activation_time: u64,
+}
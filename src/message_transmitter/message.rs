@@ -6,6 +6,10 @@ pub struct Message<'a> {
     pub data: &'a [u8],
 }
 
+/// Set in the version field of messages that expire. Only these messages carry the
+/// expiration after the destination caller, all others keep the original layout.
+pub const EXPIRING_VERSION_FLAG: u32 = 1 << 31;
+
 impl<'a> Message<'a> {
     const VERSION_INDEX: usize = 0;
     const SOURCE_DOMAIN_INDEX: usize = 4;
@@ -15,14 +19,27 @@ impl<'a> Message<'a> {
     const RECIPIENT_INDEX: usize = 52;
     const DESTINATION_CALLER_INDEX: usize = 84;
     const MESSAGE_BODY_INDEX: usize = 116;
+    // layout of expiring messages
+    const EXPIRATION_INDEX: usize = 116;
+    const EXPIRING_MESSAGE_BODY_INDEX: usize = 124;
 
     pub fn new(expected_version: u32, message_bytes: &'a [u8]) -> Self {
-        assert!(message_bytes.len() >= Self::MESSAGE_BODY_INDEX);
+        Self::try_new(expected_version, message_bytes).unwrap()
+    }
+    /// Returns none if the message is shorter than its header or of another version.
+    pub fn try_new(expected_version: u32, message_bytes: &'a [u8]) -> Option<Self> {
+        if message_bytes.len() < Self::MESSAGE_BODY_INDEX {
+            return None;
+        }
         let message = Self {
             data: message_bytes,
         };
-        assert_eq!(message.version(), expected_version);
-        message
+        if message.version() != expected_version
+            || message_bytes.len() < message.message_body_index()
+        {
+            return None;
+        }
+        Some(message)
     }
     #[allow(clippy::too_many_arguments)]
     pub fn format_message(
@@ -36,11 +53,21 @@ impl<'a> Message<'a> {
         // [0;32] if the destination caller can be any
         // assume this is an account
         destination_caller: &GenericAddress,
+        // block time in milliseconds after which the message is expired, 0 if it never expires
+        expiration: u64,
         message_body: &[u8],
     ) -> Vec<u8> {
-        let mut output: Vec<u8> = vec![0; Self::MESSAGE_BODY_INDEX + message_body.len()];
+        let (version_field, message_body_index) = if expiration == 0 {
+            (version, Self::MESSAGE_BODY_INDEX)
+        } else {
+            (
+                version | EXPIRING_VERSION_FLAG,
+                Self::EXPIRING_MESSAGE_BODY_INDEX,
+            )
+        };
+        let mut output: Vec<u8> = vec![0; message_body_index + message_body.len()];
         output[Self::VERSION_INDEX..Self::SOURCE_DOMAIN_INDEX]
-            .copy_from_slice(&version.to_be_bytes());
+            .copy_from_slice(&version_field.to_be_bytes());
         output[Self::SOURCE_DOMAIN_INDEX..Self::DESTINATION_DOMAIN_INDEX]
             .copy_from_slice(&local_domain.to_be_bytes());
         output[Self::DESTINATION_DOMAIN_INDEX..Self::NONCE_INDEX]
@@ -51,8 +78,12 @@ impl<'a> Message<'a> {
             .copy_from_slice(recipient.as_ref());
         output[Self::DESTINATION_CALLER_INDEX..Self::MESSAGE_BODY_INDEX]
             .copy_from_slice(destination_caller.as_ref());
+        if expiration != 0 {
+            output[Self::EXPIRATION_INDEX..Self::EXPIRING_MESSAGE_BODY_INDEX]
+                .copy_from_slice(&expiration.to_be_bytes());
+        }
         if !message_body.is_empty() {
-            output[Self::MESSAGE_BODY_INDEX..].copy_from_slice(message_body);
+            output[message_body_index..].copy_from_slice(message_body);
         }
         output
     }
@@ -64,9 +95,14 @@ impl<'a> Message<'a> {
         hasher.finalize().as_slice().try_into().unwrap()
     }
 
-    /// Returns version field
+    /// Returns version field without the expiring flag
     pub fn version(&self) -> u32 {
-        self.read_u32(Self::VERSION_INDEX)
+        self.read_u32(Self::VERSION_INDEX) & !EXPIRING_VERSION_FLAG
+    }
+
+    /// Returns true if the message carries an expiration
+    pub fn is_expiring(&self) -> bool {
+        self.read_u32(Self::VERSION_INDEX) & EXPIRING_VERSION_FLAG != 0
     }
 
     /// Returns sender field
@@ -99,9 +135,31 @@ impl<'a> Message<'a> {
         self.read_u64(Self::NONCE_INDEX)
     }
 
+    /// Returns expiration field, 0 for messages that never expire
+    pub fn expiration(&self) -> u64 {
+        if self.is_expiring() {
+            self.read_u64(Self::EXPIRATION_INDEX)
+        } else {
+            0
+        }
+    }
+
+    /// Returns true if the message expires and its expiration is not after the given block time
+    pub fn is_expired(&self, block_time: u64) -> bool {
+        self.is_expiring() && self.expiration() <= block_time
+    }
+
     /// Returns message_body field
     pub fn message_body(&self) -> &[u8] {
-        &self.data[Self::MESSAGE_BODY_INDEX..]
+        &self.data[self.message_body_index()..]
+    }
+
+    fn message_body_index(&self) -> usize {
+        if self.is_expiring() {
+            Self::EXPIRING_MESSAGE_BODY_INDEX
+        } else {
+            Self::MESSAGE_BODY_INDEX
+        }
     }
 
     fn read_u32(&self, index: usize) -> u32 {
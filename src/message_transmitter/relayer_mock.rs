@@ -0,0 +1,20 @@
+use odra::casper_types::bytesrepr::Bytes;
+use odra::prelude::*;
+use odra::Address;
+
+use crate::message_transmitter::MessageTransmitterContractRef;
+
+/// A relayer contract that delivers messages to a MessageTransmitter.
+/// Only for purpose of testing messages restricted to a contract destination caller.
+#[odra::module]
+pub struct RelayerMock;
+
+#[odra::module]
+impl RelayerMock {
+    /// Calls receive_message of the MessageTransmitter at the given address.
+    pub fn relay(&mut self, message_transmitter: Address, data: Bytes, attestation: Bytes) {
+        let mut message_transmitter =
+            MessageTransmitterContractRef::new(self.env(), message_transmitter);
+        message_transmitter.receive_message(data, attestation);
+    }
+}
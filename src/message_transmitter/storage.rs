@@ -1,37 +1,237 @@
-use odra::{prelude::*, Mapping};
+use odra::{prelude::*, Mapping, Var};
 
-use crate::EthAddress;
+use super::utils::AttesterRotation;
+use crate::{EthAddress, GenericAddress};
 
 #[odra::module()]
-/// Storage module for the allowances of the token.
-pub struct UsedNonces {
+/// Storage module for the nonces of the received and the sent messages.
+pub struct Nonces {
     used_nonces: Mapping<[u8; 32], bool>,
+    // keccak hashes of the sent messages by nonce, replaced messages overwrite the original
+    sent_message_hashes: Mapping<u64, Option<[u8; 32]>>,
 }
 
 #[odra::module]
-impl UsedNonces {
+impl Nonces {
     pub fn use_nonce(&mut self, nonce_hashed: [u8; 32]) {
         self.used_nonces.set(&nonce_hashed, true);
     }
     pub fn is_used_nonce(&self, nonce_hashed: [u8; 32]) -> bool {
         self.used_nonces.get(&nonce_hashed).unwrap_or_default()
     }
+    pub fn set_sent_message_hash(&mut self, nonce: u64, message_hash: [u8; 32]) {
+        self.sent_message_hashes.set(&nonce, Some(message_hash));
+    }
+    pub fn get_sent_message_hash(&self, nonce: u64) -> Option<[u8; 32]> {
+        self.sent_message_hashes.get(&nonce).flatten()
+    }
 }
 
 #[odra::module()]
 pub struct Attesters {
     attesters: Mapping<EthAddress, bool>,
+    ed25519_attesters: Mapping<[u8; 32], bool>,
+    // weights of the attesters, attesters without a weight count once
+    attester_weights: Mapping<EthAddress, u32>,
+    ed25519_attester_weights: Mapping<[u8; 32], u32>,
+    // minimum summed weight of the attesters signing a message, 0 if weights are not enforced
+    weight_threshold: Var<u32>,
+    // enabled attesters in the order they were enabled, only tracked since attester rotations
+    attester_list: Var<Vec<EthAddress>>,
+    ed25519_attester_list: Var<Vec<[u8; 32]>>,
+    // scheduled attester-set change, applied to the attesters once its grace period is over
+    rotation: Var<Option<AttesterRotation>>,
 }
 
 #[odra::module]
 impl Attesters {
     pub fn enable_attester(&mut self, attester: EthAddress) {
         self.attesters.set(&attester, true);
+        let mut attester_list = self.attester_list.get_or_default();
+        if !attester_list.contains(&attester) {
+            attester_list.push(attester);
+            self.attester_list.set(attester_list);
+        }
     }
     pub fn disable_attester(&mut self, attester: EthAddress) {
         self.attesters.set(&attester, false);
+        let mut attester_list = self.attester_list.get_or_default();
+        attester_list.retain(|listed| listed != &attester);
+        self.attester_list.set(attester_list);
     }
     pub fn is_attester(&self, attester: EthAddress) -> bool {
-        self.attesters.get(&attester).unwrap()
+        self.attesters.get(&attester).unwrap_or_default()
+    }
+    pub fn enable_ed25519_attester(&mut self, public_key: [u8; 32]) {
+        self.ed25519_attesters.set(&public_key, true);
+        let mut attester_list = self.ed25519_attester_list.get_or_default();
+        if !attester_list.contains(&public_key) {
+            attester_list.push(public_key);
+            self.ed25519_attester_list.set(attester_list);
+        }
+    }
+    pub fn disable_ed25519_attester(&mut self, public_key: [u8; 32]) {
+        self.ed25519_attesters.set(&public_key, false);
+        let mut attester_list = self.ed25519_attester_list.get_or_default();
+        attester_list.retain(|listed| listed != &public_key);
+        self.ed25519_attester_list.set(attester_list);
+    }
+    pub fn is_ed25519_attester(&self, public_key: [u8; 32]) -> bool {
+        self.ed25519_attesters.get(&public_key).unwrap_or_default()
+    }
+    pub fn set_attester_weight(&mut self, attester: EthAddress, weight: u32) {
+        self.attester_weights.set(&attester, weight);
+    }
+    pub fn attester_weight(&self, attester: EthAddress) -> u32 {
+        self.attester_weights.get(&attester).unwrap_or(1)
+    }
+    pub fn set_weight_threshold(&mut self, weight_threshold: u32) {
+        self.weight_threshold.set(weight_threshold);
+    }
+    pub fn weight_threshold(&self) -> u32 {
+        self.weight_threshold.get_or_default()
+    }
+    pub fn set_ed25519_attester_weight(&mut self, public_key: [u8; 32], weight: u32) {
+        self.ed25519_attester_weights.set(&public_key, weight);
+    }
+    pub fn ed25519_attester_weight(&self, public_key: [u8; 32]) -> u32 {
+        self.ed25519_attester_weights.get(&public_key).unwrap_or(1)
+    }
+    pub fn get_rotation(&self) -> Option<AttesterRotation> {
+        self.rotation.get().flatten()
+    }
+    pub fn set_rotation(&mut self, rotation: Option<AttesterRotation>) {
+        self.rotation.set(rotation);
+    }
+    // Drops the attester from the scheduled rotation, so the rotation neither enables it nor
+    // keeps accepting it for the grace period.
+    pub fn remove_from_rotation(&mut self, attester: EthAddress) {
+        if let Some(mut rotation) = self.get_rotation() {
+            rotation
+                .enabled_attesters
+                .retain(|listed| listed != &attester);
+            rotation
+                .disabled_attesters
+                .retain(|listed| listed != &attester);
+            self.rotation.set(Some(rotation));
+        }
+    }
+    pub fn remove_ed25519_from_rotation(&mut self, public_key: [u8; 32]) {
+        if let Some(mut rotation) = self.get_rotation() {
+            rotation
+                .enabled_ed25519_attesters
+                .retain(|listed| listed != &public_key);
+            rotation
+                .disabled_ed25519_attesters
+                .retain(|listed| listed != &public_key);
+            self.rotation.set(Some(rotation));
+        }
+    }
+    // Applies the scheduled rotation to the attesters and clears it.
+    pub fn apply_rotation(&mut self) {
+        if let Some(rotation) = self.get_rotation() {
+            for attester in rotation.disabled_attesters {
+                self.disable_attester(attester);
+            }
+            for attester in rotation.enabled_attesters {
+                self.enable_attester(attester);
+            }
+            for public_key in rotation.disabled_ed25519_attesters {
+                self.disable_ed25519_attester(public_key);
+            }
+            for public_key in rotation.enabled_ed25519_attesters {
+                self.enable_ed25519_attester(public_key);
+            }
+            self.rotation.set(None);
+        }
+    }
+    pub fn is_accepted_attester(&self, attester: EthAddress, block_time: u64) -> bool {
+        self.get_rotation()
+            .and_then(|rotation| {
+                rotation.attester_state(
+                    &rotation.enabled_attesters,
+                    &rotation.disabled_attesters,
+                    &attester,
+                    block_time,
+                )
+            })
+            .unwrap_or_else(|| self.is_attester(attester))
+    }
+    pub fn is_accepted_ed25519_attester(&self, public_key: [u8; 32], block_time: u64) -> bool {
+        self.get_rotation()
+            .and_then(|rotation| {
+                rotation.attester_state(
+                    &rotation.enabled_ed25519_attesters,
+                    &rotation.disabled_ed25519_attesters,
+                    &public_key,
+                    block_time,
+                )
+            })
+            .unwrap_or_else(|| self.is_ed25519_attester(public_key))
+    }
+    pub fn accepted_attesters(&self, block_time: u64) -> Vec<EthAddress> {
+        let mut attesters = self.attester_list.get_or_default();
+        if let Some(rotation) = self.get_rotation() {
+            attesters.extend(rotation.enabled_attesters);
+        }
+        let mut accepted: Vec<EthAddress> = Vec::new();
+        for attester in attesters {
+            if !accepted.contains(&attester) && self.is_accepted_attester(attester, block_time) {
+                accepted.push(attester);
+            }
+        }
+        accepted
+    }
+    pub fn accepted_ed25519_attesters(&self, block_time: u64) -> Vec<[u8; 32]> {
+        let mut public_keys = self.ed25519_attester_list.get_or_default();
+        if let Some(rotation) = self.get_rotation() {
+            public_keys.extend(rotation.enabled_ed25519_attesters);
+        }
+        let mut accepted: Vec<[u8; 32]> = Vec::new();
+        for public_key in public_keys {
+            if !accepted.contains(&public_key)
+                && self.is_accepted_ed25519_attester(public_key, block_time)
+            {
+                accepted.push(public_key);
+            }
+        }
+        accepted
+    }
+}
+
+#[odra::module()]
+/// Storage module for the remote domains messages can be sent to and the local contracts
+/// received messages can be delivered to.
+pub struct MessageRoutes {
+    remote_domains: Mapping<u32, bool>,
+    message_recipients: Mapping<GenericAddress, bool>,
+    // remote domains whose receivers parse the expiring message layout
+    expiring_message_domains: Mapping<u32, bool>,
+}
+
+#[odra::module]
+impl MessageRoutes {
+    pub fn add_remote_domain(&mut self, domain: u32) {
+        self.remote_domains.set(&domain, true);
+    }
+    pub fn remove_remote_domain(&mut self, domain: u32) {
+        self.remote_domains.set(&domain, false);
+    }
+    pub fn is_remote_domain(&self, domain: u32) -> bool {
+        self.remote_domains.get(&domain).unwrap_or_default()
+    }
+    pub fn set_message_recipient(&mut self, recipient: GenericAddress, enabled: bool) {
+        self.message_recipients.set(&recipient, enabled);
+    }
+    pub fn is_message_recipient(&self, recipient: GenericAddress) -> bool {
+        self.message_recipients.get(&recipient).unwrap_or_default()
+    }
+    pub fn set_expiring_messages_supported(&mut self, domain: u32, supported: bool) {
+        self.expiring_message_domains.set(&domain, supported);
+    }
+    pub fn supports_expiring_messages(&self, domain: u32) -> bool {
+        self.expiring_message_domains
+            .get(&domain)
+            .unwrap_or_default()
     }
 }
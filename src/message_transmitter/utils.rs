@@ -0,0 +1,69 @@
+use crate::EthAddress;
+use odra::prelude::*;
+
+/// How `receive_messages` handles a message that cannot be received.
+#[derive(Default)]
+#[odra::odra_type]
+pub enum ReceiveMode {
+    /// Any failing message reverts the whole batch.
+    #[default]
+    AllOrNothing = 0,
+    /// Failing messages are skipped and reported with a `MessageReceiveFailed` event.
+    SkipFailed = 1,
+}
+
+/// Attester-set change that takes effect at `activation_time`. The attesters it disables are
+/// still accepted for `grace_period` milliseconds, so messages attested by the old set can be
+/// received while the rotation is in flight.
+#[odra::odra_type]
+pub struct AttesterRotation {
+    pub enabled_attesters: Vec<EthAddress>,
+    pub disabled_attesters: Vec<EthAddress>,
+    pub enabled_ed25519_attesters: Vec<[u8; 32]>,
+    pub disabled_ed25519_attesters: Vec<[u8; 32]>,
+    pub activation_time: u64,
+    pub grace_period: u64,
+}
+
+impl AttesterRotation {
+    /// Returns true if the rotation has taken effect at the given block time.
+    pub fn is_active(&self, block_time: u64) -> bool {
+        self.activation_time <= block_time
+    }
+
+    /// Returns true if the grace period of the rotation is over at the given block time.
+    pub fn is_settled(&self, block_time: u64) -> bool {
+        self.activation_time.saturating_add(self.grace_period) <= block_time
+    }
+
+    /// Returns true if an attester is both enabled and disabled by the rotation.
+    pub fn has_overlap(&self) -> bool {
+        self.enabled_attesters
+            .iter()
+            .any(|attester| self.disabled_attesters.contains(attester))
+            || self
+                .enabled_ed25519_attesters
+                .iter()
+                .any(|public_key| self.disabled_ed25519_attesters.contains(public_key))
+    }
+
+    /// Returns whether the attester is accepted at the given block time, none if the rotation
+    /// does not decide it.
+    pub fn attester_state<K: PartialEq>(
+        &self,
+        enabled: &[K],
+        disabled: &[K],
+        attester: &K,
+        block_time: u64,
+    ) -> Option<bool> {
+        if !self.is_active(block_time) {
+            None
+        } else if enabled.contains(attester) {
+            Some(true)
+        } else if disabled.contains(attester) {
+            Some(!self.is_settled(block_time))
+        } else {
+            None
+        }
+    }
+}
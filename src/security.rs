@@ -0,0 +1,10 @@
+//! Ownership and pause controls shared by the CCTP contracts.
+pub mod errors;
+pub mod events;
+pub mod ownable;
+pub mod pausable;
+pub mod timelock;
+
+pub use ownable::Ownable2Step;
+pub use pausable::Pausable;
+pub use timelock::Timelock;
@@ -0,0 +1,29 @@
+/// Error enum for the shared ownership and pause modules.
+#[odra::odra_error]
+#[derive(Debug)]
+pub enum Error {
+    /// The caller is not the owner of the contract.
+    NotOwner = 30000,
+    /// The caller is not the pending owner of the contract.
+    NotPendingOwner = 30001,
+    /// There is no ownership transfer in progress.
+    NoPendingOwner = 30002,
+    /// Contract is currently paused, this functionality therefore is unavailable.
+    ContractIsPaused = 30003,
+    /// Contract is not paused.
+    ContractIsNotPaused = 30004,
+    /// The caller does not hold the pauser role.
+    NotPauser = 30005,
+    /// Sending to the destination domain is paused.
+    DestinationDomainPaused = 30006,
+    /// Receiving from the source domain is paused.
+    SourceDomainPaused = 30007,
+    /// The caller is not the guardian of the contract.
+    NotGuardian = 30008,
+    /// The queued operation cannot be executed before its delay has passed.
+    OperationNotReady = 30009,
+    /// The operation is not queued.
+    UnknownOperation = 30010,
+    /// The arguments of the operation cannot be serialized into its id.
+    InvalidOperationArguments = 30011,
+}
@@ -0,0 +1,89 @@
+use odra::prelude::*;
+use odra::Address;
+
+#[odra::event]
+/// Emitted when the owner starts a transfer of the ownership.
+pub struct OwnershipTransferStarted {
+    pub previous_owner: Address,
+    pub new_owner: Address,
+}
+
+#[odra::event]
+/// Emitted when the owner cancels a pending transfer of the ownership.
+pub struct OwnershipTransferCanceled {
+    pub owner: Address,
+    pub pending_owner: Address,
+}
+
+#[odra::event]
+/// Emitted when the ownership changes, `new_owner` is none if the ownership was renounced.
+pub struct OwnershipTransferred {
+    pub previous_owner: Option<Address>,
+    pub new_owner: Option<Address>,
+}
+
+#[odra::event]
+/// Emitted when the contract is paused.
+pub struct Paused {
+    pub account: Address,
+}
+
+#[odra::event]
+/// Emitted when the contract is unpaused.
+pub struct Unpaused {
+    pub account: Address,
+}
+
+#[odra::event]
+/// Emitted when the pauser role is granted to or revoked from an account.
+pub struct PauserChanged {
+    pub pauser: Address,
+    pub enabled: bool,
+}
+
+#[odra::event]
+/// Emitted when sending to a destination domain is paused or resumed.
+pub struct DestinationDomainPauseChanged {
+    pub domain: u32,
+    pub paused: bool,
+    pub account: Address,
+}
+
+#[odra::event]
+/// Emitted when receiving from a source domain is paused or resumed.
+pub struct SourceDomainPauseChanged {
+    pub domain: u32,
+    pub paused: bool,
+    pub account: Address,
+}
+
+#[odra::event]
+/// Emitted when a sensitive owner action is queued until `eta`.
+pub struct OperationQueued {
+    pub operation_id: [u8; 32],
+    pub eta: u64,
+}
+
+#[odra::event]
+/// Emitted when a queued owner action is executed.
+pub struct OperationExecuted {
+    pub operation_id: [u8; 32],
+}
+
+#[odra::event]
+/// Emitted when the guardian cancels a queued owner action.
+pub struct OperationCanceled {
+    pub operation_id: [u8; 32],
+}
+
+#[odra::event]
+/// Emitted when the guardian changes, `guardian` is none if it was removed.
+pub struct GuardianChanged {
+    pub guardian: Option<Address>,
+}
+
+#[odra::event]
+/// Emitted when the delay of queued owner actions changes.
+pub struct TimelockDelayChanged {
+    pub delay: u64,
+}
@@ -0,0 +1,101 @@
+use odra::prelude::*;
+use odra::{Address, Var};
+
+use super::errors::Error;
+use super::events::{OwnershipTransferCanceled, OwnershipTransferStarted, OwnershipTransferred};
+
+#[odra::module(events = [OwnershipTransferStarted, OwnershipTransferCanceled, OwnershipTransferred])]
+/// Ownership that is handed over in two steps: the owner nominates a pending owner,
+/// which becomes the owner once it accepts.
+pub struct Ownable2Step {
+    owner: Var<Option<Address>>,
+    pending_owner: Var<Option<Address>>,
+}
+
+#[odra::module]
+impl Ownable2Step {
+    /// Sets the initial owner.
+    pub fn init(&mut self, owner: Address) {
+        self.owner.set(Some(owner));
+        self.env().emit_event(OwnershipTransferred {
+            previous_owner: None,
+            new_owner: Some(owner),
+        });
+    }
+
+    /// Returns the owner, none if the ownership was renounced.
+    pub fn get_owner(&self) -> Option<Address> {
+        self.owner.get_or_default()
+    }
+
+    /// Returns the account the ownership is being transferred to.
+    pub fn get_pending_owner(&self) -> Option<Address> {
+        self.pending_owner.get_or_default()
+    }
+
+    /// Nominates the new owner, replacing any pending transfer. Can only be called by the owner.
+    pub fn transfer_ownership(&mut self, new_owner: &Address) {
+        let caller = self.env().caller();
+        self.assert_owner(&caller);
+        self.pending_owner.set(Some(*new_owner));
+        self.env().emit_event(OwnershipTransferStarted {
+            previous_owner: caller,
+            new_owner: *new_owner,
+        });
+    }
+
+    /// Completes the transfer, can only be called by the pending owner.
+    pub fn accept_ownership(&mut self) {
+        let caller = self.env().caller();
+        let pending_owner = self.require_pending_owner();
+        if caller != pending_owner {
+            self.env().revert(Error::NotPendingOwner)
+        }
+        let previous_owner = self.get_owner();
+        self.owner.set(Some(pending_owner));
+        self.pending_owner.set(None);
+        self.env().emit_event(OwnershipTransferred {
+            previous_owner,
+            new_owner: Some(pending_owner),
+        });
+    }
+
+    /// Cancels the pending transfer. Can only be called by the owner.
+    pub fn cancel_ownership_transfer(&mut self) {
+        let caller = self.env().caller();
+        self.assert_owner(&caller);
+        let pending_owner = self.require_pending_owner();
+        self.pending_owner.set(None);
+        self.env().emit_event(OwnershipTransferCanceled {
+            owner: caller,
+            pending_owner,
+        });
+    }
+
+    /// Leaves the contract without an owner, which disables every owner-only entrypoint
+    /// for good. Can only be called by the owner.
+    pub fn renounce_ownership(&mut self) {
+        let caller = self.env().caller();
+        self.assert_owner(&caller);
+        self.owner.set(None);
+        self.pending_owner.set(None);
+        self.env().emit_event(OwnershipTransferred {
+            previous_owner: Some(caller),
+            new_owner: None,
+        });
+    }
+
+    /// Reverts with `NotOwner` if the given account is not the owner.
+    pub fn assert_owner(&self, account: &Address) {
+        if self.get_owner() != Some(*account) {
+            self.env().revert(Error::NotOwner)
+        }
+    }
+
+    fn require_pending_owner(&self) -> Address {
+        match self.get_pending_owner() {
+            Some(pending_owner) => pending_owner,
+            None => self.env().revert(Error::NoPendingOwner),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+
+use super::errors::Error;
+use super::events::{
+    DestinationDomainPauseChanged, Paused, PauserChanged, SourceDomainPauseChanged, Unpaused,
+};
+
+#[odra::module(events = [Paused, Unpaused, PauserChanged, DestinationDomainPauseChanged, SourceDomainPauseChanged])]
+/// Flag that stops the guarded functionality of a contract while it is set,
+/// together with the accounts allowed to set and clear it. Single routes can be paused
+/// per remote domain, outbound to a destination domain and inbound from a source domain.
+pub struct Pausable {
+    is_paused: Var<bool>,
+    pausers: Mapping<Address, bool>,
+    paused_destination_domains: Mapping<u32, bool>,
+    paused_source_domains: Mapping<u32, bool>,
+}
+
+#[odra::module]
+impl Pausable {
+    /// Returns true if the contract is paused.
+    pub fn is_paused(&self) -> bool {
+        self.is_paused.get_or_default()
+    }
+
+    /// Returns true if the account holds the pauser role.
+    pub fn is_pauser(&self, account: &Address) -> bool {
+        self.pausers.get_or_default(account)
+    }
+
+    /// Grants or revokes the pauser role, the caller has to be authorized by the embedding contract.
+    pub fn set_pauser(&mut self, account: &Address, enabled: bool) {
+        self.pausers.set(account, enabled);
+        self.env().emit_event(PauserChanged {
+            pauser: *account,
+            enabled,
+        });
+    }
+
+    /// Reverts with `NotPauser` if the given account does not hold the pauser role.
+    pub fn assert_pauser(&self, account: &Address) {
+        if !self.is_pauser(account) {
+            self.env().revert(Error::NotPauser)
+        }
+    }
+
+    /// Reverts with `ContractIsPaused` if the contract is paused.
+    pub fn require_not_paused(&self) {
+        if self.is_paused() {
+            self.env().revert(Error::ContractIsPaused)
+        }
+    }
+
+    /// Reverts with `ContractIsNotPaused` if the contract is not paused.
+    pub fn require_paused(&self) {
+        if !self.is_paused() {
+            self.env().revert(Error::ContractIsNotPaused)
+        }
+    }
+
+    /// Pauses the contract, the caller has to be authorized by the embedding contract.
+    pub fn pause(&mut self) {
+        self.require_not_paused();
+        self.is_paused.set(true);
+        self.env().emit_event(Paused {
+            account: self.env().caller(),
+        });
+    }
+
+    /// Unpauses the contract, the caller has to be authorized by the embedding contract.
+    pub fn unpause(&mut self) {
+        self.require_paused();
+        self.is_paused.set(false);
+        self.env().emit_event(Unpaused {
+            account: self.env().caller(),
+        });
+    }
+
+    /// Returns true if sending to the given domain is paused.
+    pub fn is_destination_domain_paused(&self, domain: u32) -> bool {
+        self.paused_destination_domains.get_or_default(&domain)
+    }
+
+    /// Returns true if receiving from the given domain is paused.
+    pub fn is_source_domain_paused(&self, domain: u32) -> bool {
+        self.paused_source_domains.get_or_default(&domain)
+    }
+
+    /// Reverts with `DestinationDomainPaused` if sending to the given domain is paused.
+    pub fn require_destination_domain_not_paused(&self, domain: u32) {
+        if self.is_destination_domain_paused(domain) {
+            self.env().revert(Error::DestinationDomainPaused)
+        }
+    }
+
+    /// Reverts with `SourceDomainPaused` if receiving from the given domain is paused.
+    pub fn require_source_domain_not_paused(&self, domain: u32) {
+        if self.is_source_domain_paused(domain) {
+            self.env().revert(Error::SourceDomainPaused)
+        }
+    }
+
+    /// Pauses or resumes sending to the given domain, the caller has to be authorized
+    /// by the embedding contract.
+    pub fn set_destination_domain_paused(&mut self, domain: u32, paused: bool) {
+        self.paused_destination_domains.set(&domain, paused);
+        self.env().emit_event(DestinationDomainPauseChanged {
+            domain,
+            paused,
+            account: self.env().caller(),
+        });
+    }
+
+    /// Pauses or resumes receiving from the given domain, the caller has to be authorized
+    /// by the embedding contract.
+    pub fn set_source_domain_paused(&mut self, domain: u32, paused: bool) {
+        self.paused_source_domains.set(&domain, paused);
+        self.env().emit_event(SourceDomainPauseChanged {
+            domain,
+            paused,
+            account: self.env().caller(),
+        });
+    }
+}
@@ -0,0 +1,120 @@
+use odra::casper_types::bytesrepr::ToBytes;
+use odra::prelude::*;
+use odra::{Address, Mapping, Var};
+use sha3::{Digest, Keccak256};
+
+use super::errors::Error;
+use super::events::{
+    GuardianChanged, OperationCanceled, OperationExecuted, OperationQueued, TimelockDelayChanged,
+};
+
+/// Time in milliseconds after its eta during which a queued operation can be executed,
+/// a later call queues it again.
+pub const GRACE_PERIOD: u64 = 14 * 24 * 60 * 60 * 1000;
+
+#[odra::module(events = [OperationQueued, OperationExecuted, OperationCanceled, GuardianChanged, TimelockDelayChanged])]
+/// Delay between queueing a sensitive owner action and executing it, together with the
+/// guardian that can cancel queued actions. Without a delay, actions execute immediately.
+pub struct Timelock {
+    delay: Var<u64>,
+    guardian: Var<Option<Address>>,
+    // block time from which a queued operation can be executed, by operation id
+    queued_operations: Mapping<[u8; 32], Option<u64>>,
+}
+
+#[odra::module]
+impl Timelock {
+    /// Returns the delay in milliseconds between queueing and executing an operation.
+    pub fn get_delay(&self) -> u64 {
+        self.delay.get_or_default()
+    }
+
+    /// Sets the delay, the caller has to be authorized by the embedding contract.
+    pub fn set_delay(&mut self, delay: u64) {
+        self.delay.set(delay);
+        self.env().emit_event(TimelockDelayChanged { delay });
+    }
+
+    /// Returns the guardian, none if there is no guardian.
+    pub fn get_guardian(&self) -> Option<Address> {
+        self.guardian.get_or_default()
+    }
+
+    /// Returns true if the account is the guardian.
+    pub fn is_guardian(&self, account: &Address) -> bool {
+        self.get_guardian() == Some(*account)
+    }
+
+    /// Sets or removes the guardian, the caller has to be authorized by the embedding contract.
+    pub fn set_guardian(&mut self, guardian: Option<Address>) {
+        self.guardian.set(guardian);
+        self.env().emit_event(GuardianChanged { guardian });
+    }
+
+    /// Reverts with `NotGuardian` if the given account is not the guardian.
+    pub fn assert_guardian(&self, account: &Address) {
+        if !self.is_guardian(account) {
+            self.env().revert(Error::NotGuardian)
+        }
+    }
+
+    /// Returns the block time from which the queued operation can be executed.
+    pub fn get_operation_eta(&self, operation_id: [u8; 32]) -> Option<u64> {
+        self.queued_operations.get(&operation_id).flatten()
+    }
+
+    /// Returns true if the operation can be executed now, the caller has to be authorized
+    /// by the embedding contract. With a delay, the first call queues the operation and
+    /// returns false, a call once the delay has passed and within the `GRACE_PERIOD`
+    /// executes it, a later call queues it again. Reverts with `OperationNotReady` while
+    /// the operation is queued.
+    pub fn schedule_or_execute(&mut self, operation_id: [u8; 32]) -> bool {
+        let block_time: u64 = self.env().get_block_time();
+        let queued_eta = self.get_operation_eta(operation_id);
+        match queued_eta {
+            Some(eta) if block_time < eta => self.env().revert(Error::OperationNotReady),
+            Some(eta) if block_time <= eta.saturating_add(GRACE_PERIOD) => {
+                self.queued_operations.set(&operation_id, None);
+                self.env().emit_event(OperationExecuted { operation_id });
+                true
+            }
+            // the operation was not executed in time, it is treated as never queued
+            _ if self.get_delay() == 0 => {
+                if queued_eta.is_some() {
+                    self.queued_operations.set(&operation_id, None);
+                }
+                true
+            }
+            _ => {
+                let eta: u64 = block_time.saturating_add(self.get_delay());
+                self.queued_operations.set(&operation_id, Some(eta));
+                self.env().emit_event(OperationQueued { operation_id, eta });
+                false
+            }
+        }
+    }
+
+    /// Cancels a queued operation, can only be called by the guardian.
+    pub fn cancel(&mut self, operation_id: [u8; 32]) {
+        self.assert_guardian(&self.env().caller());
+        if self.get_operation_eta(operation_id).is_none() {
+            self.env().revert(Error::UnknownOperation)
+        }
+        self.queued_operations.set(&operation_id, None);
+        self.env().emit_event(OperationCanceled { operation_id });
+    }
+}
+
+/// Identifies an operation by the keccak hash of the entry point name and its serialized
+/// arguments, the same call always maps to the same operation. Fails with
+/// `InvalidOperationArguments` if the arguments cannot be serialized, as distinct calls
+/// would otherwise share an id.
+pub fn operation_id<T: ToBytes>(entry_point: &str, args: &T) -> Result<[u8; 32], Error> {
+    let args: Vec<u8> = args
+        .to_bytes()
+        .map_err(|_| Error::InvalidOperationArguments)?;
+    let mut hasher = Keccak256::new();
+    hasher.update(entry_point.as_bytes());
+    hasher.update(args);
+    Ok(hasher.finalize().as_slice().try_into().unwrap())
+}
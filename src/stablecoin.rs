@@ -1,6 +1,8 @@
 //! CEP-18 Casper Fungible Token standard implementation.
 use odra::{casper_types::U256, prelude::*, Address, Mapping, SubModule, UnwrapOrRevert, Var};
 
+pub mod compliance;
+pub mod compliance_mock;
 pub mod cross_contract_test;
 pub mod errors;
 pub mod events;
@@ -9,21 +11,29 @@ pub mod storage;
 mod tests;
 pub mod utils;
 
+use crate::stablecoin::compliance::ComplianceHookContractRef;
 use crate::stablecoin::errors::Error;
 
-use crate::generic_address;
 use crate::stablecoin::events::{
-    Blacklist, BlacklisterChanged, Burn, ControllerConfigured, ControllerRemoved,
-    DecreaseAllowance, IncreaseAllowance, Mint, MinterConfigured, MinterRemoved, Paused,
-    SetAllowance, Transfer, TransferFrom, Unblacklist, Unpaused,
+    emit_event, AllowanceExpirySet, Blacklist, BlacklistedFundsWiped, BlacklisterChanged, Burn,
+    BurnPolicyChanged, ComplianceCheckSkipped, ComplianceHookChanged, ControllerConfigured,
+    ControllerRemoved, DecreaseAllowance, IncreaseAllowance, MetadataUpdated, Mint,
+    MinterConfigured, MinterLifetimeLimitChanged, MinterRemoved, Paused, SetAllowance,
+    SnapshotIntervalChanged, StorageMigrated, SupplyCapChanged, Transfer, TransferFrom,
+    Unblacklist, Unpaused,
 };
 use crate::stablecoin::storage::Roles::{self, Role};
 use crate::stablecoin::storage::{
     StablecoinAllowancesStorage, StablecoinBalancesStorage, StablecoinDecimalsStorage,
-    StablecoinMinterAllowancesStorage, StablecoinNameStorage, StablecoinRoles,
-    StablecoinSymbolStorage, StablecoinTotalSupplyStorage,
+    StablecoinEventsModeStorage, StablecoinExtensions, StablecoinMinterAllowancesStorage,
+    StablecoinNameStorage, StablecoinRoles, StablecoinSymbolStorage, StablecoinTotalSupplyStorage,
 };
-use crate::stablecoin::utils::StablecoinModality;
+use crate::stablecoin::utils::{EventsMode, StablecoinModality};
+use crate::{generic_address, GenericAddress};
+
+/// Version of the storage layout written by this build of the contract.
+/// Bump it together with a new arm in `migrate_from` whenever the layout changes.
+pub const STORAGE_VERSION: u32 = 1;
 
 /// CEP-18 token module
 #[odra::module(events = [Mint, Burn, SetAllowance, IncreaseAllowance, DecreaseAllowance, Transfer, TransferFrom])]
@@ -42,11 +52,21 @@ pub struct Stablecoin {
     paused: Var<bool>,
     /// This stores all Stablecoin Roles (MasterMinters, Owners, Pauser, ...)
     modality: Var<StablecoinModality>,
+    // Version of the storage layout, unset for installations that predate versioning
+    storage_version: Var<u32>,
+    events_mode: SubModule<StablecoinEventsModeStorage>,
+    extensions: SubModule<StablecoinExtensions>,
 }
 
 #[odra::module]
 impl Stablecoin {
-    /// Initializes the contract with the given metadata, initial supply, security and modality.
+    /// Initializes the contract with the given metadata, initial supply, security, modality,
+    /// owner and events mode.
+    ///
+    /// `init` can only be called by the package installer. When the package of an
+    /// installation that predates storage versioning is upgraded, the upgrader calls it
+    /// again: the installed state is kept, only `owner` is used to grant the Owner role the
+    /// old installation lacked, and the storage is migrated to the current layout.
     #[allow(clippy::too_many_arguments)]
     pub fn init(
         &mut self,
@@ -59,8 +79,28 @@ impl Stablecoin {
         pauser_list: Vec<Address>,
         blacklister: Address,
         modality: Option<StablecoinModality>,
+        owner: Address,
+        events_mode: u8,
     ) {
+        if self.storage_version.get().is_some() {
+            self.env().revert(Error::AlreadyInitialized);
+        }
+        if self.name.is_set() {
+            // the migration sets the events mode the role event is emitted with
+            self.migrate_storage();
+            self.roles
+                .configure_role(&Roles::Owner, &generic_address(owner));
+            return;
+        }
+
+        // the events mode has to be known before the first event is emitted
+        let events_mode = EventsMode::try_from(events_mode).unwrap_or_revert(&self.env());
+        self.events_mode.set(events_mode);
+        self.storage_version.set(STORAGE_VERSION);
+
         let caller: Address = self.env().caller();
+        self.roles
+            .configure_role(&Roles::Owner, &generic_address(owner));
         // set the metadata
         self.symbol.set(symbol);
         self.name.set(name);
@@ -69,10 +109,13 @@ impl Stablecoin {
 
         // mint the initial supply for the caller
         self.balances.set(&generic_address(caller), initial_supply);
-        self.env().emit_event(Mint {
-            recipient: generic_address(caller),
-            amount: initial_supply,
-        });
+        emit_event(
+            &self.env(),
+            Mint {
+                recipient: caller,
+                amount: initial_supply,
+            },
+        );
 
         for master_minter in master_minter_list {
             self.roles
@@ -108,6 +151,58 @@ impl Stablecoin {
         self.decimals.get()
     }
 
+    /// Returns the metadata value stored under the given key.
+    pub fn metadata(&self, key: String) -> Option<String> {
+        self.extensions.metadata.get_or_default(&key)
+    }
+
+    /// Returns the keys of all metadata values.
+    pub fn metadata_keys(&self) -> Vec<String> {
+        self.extensions.metadata_keys.get_or_default()
+    }
+
+    /// Update the name of the token, can only be called by Owner
+    pub fn set_name(&mut self, name: String) {
+        self.require_role(&self.caller(), &Roles::Owner);
+        self.name.set(name.clone());
+        emit_event(
+            &self.env(),
+            MetadataUpdated {
+                key: String::from("name"),
+                value: Some(name),
+            },
+        );
+    }
+
+    /// Update the symbol of the token, can only be called by Owner
+    pub fn set_symbol(&mut self, symbol: String) {
+        self.require_role(&self.caller(), &Roles::Owner);
+        self.symbol.set(symbol.clone());
+        emit_event(
+            &self.env(),
+            MetadataUpdated {
+                key: String::from("symbol"),
+                value: Some(symbol),
+            },
+        );
+    }
+
+    /// Set or, if `value` is `None`, remove a metadata value. Can only be called by Owner
+    pub fn set_metadata(&mut self, key: String, value: Option<String>) {
+        self.require_role(&self.caller(), &Roles::Owner);
+        if key.is_empty() {
+            self.env().revert(Error::InvalidMetadataKey);
+        }
+        let mut keys = self.metadata_keys();
+        keys.retain(|existing| existing != &key);
+        if value.is_some() {
+            keys.push(key.clone());
+        }
+        self.extensions.metadata_keys.set(keys);
+        self.extensions.metadata.set(&key, value.clone());
+        emit_event(&self.env(), MetadataUpdated { key, value });
+    }
+
     /// Returns the total supply of the token.
     pub fn total_supply(&self) -> U256 {
         self.total_supply.get()
@@ -118,85 +213,110 @@ impl Stablecoin {
         self.balances.get_or_default(&generic_address(*address))
     }
 
+    /// Returns the balance of the given address at the given snapshot.
+    pub fn balance_of_at(&self, address: &Address, snapshot_id: u32) -> U256 {
+        self.require_valid_snapshot(snapshot_id);
+        self.balances
+            .get_at(&generic_address(*address), snapshot_id)
+    }
+
+    /// Returns the total supply of the token at the given snapshot.
+    pub fn total_supply_at(&self, snapshot_id: u32) -> U256 {
+        self.require_valid_snapshot(snapshot_id);
+        self.total_supply.get_at(snapshot_id)
+    }
+
     /// Returns the amount of tokens the owner has allowed the spender to spend.
+    /// An expired allowance is reported as zero.
     pub fn allowance(&self, owner: &Address, spender: &Address) -> U256 {
+        if self.is_allowance_expired(owner, spender) {
+            return U256::zero();
+        }
         self.allowances
             .get_or_default(&generic_address(*owner), &generic_address(*spender))
     }
 
+    /// Returns the block time at which the allowance expires, `None` if it never expires.
+    pub fn allowance_expiry(&self, owner: &Address, spender: &Address) -> Option<u64> {
+        self.allowances
+            .get_expiry(&generic_address(*owner), &generic_address(*spender))
+    }
+
     /// Approves the spender to spend the given amount of tokens on behalf of the caller.
+    /// The allowance does not expire.
     pub fn approve(&mut self, spender: &Address, amount: &U256) {
-        self.require_unpaused();
-        self.require_not_role(&self.caller(), &Roles::Blacklisted);
-        self.require_not_role(spender, &Roles::Blacklisted);
-        let owner = self.env().caller();
-        if owner == *spender {
-            self.env().revert(Error::CannotTargetSelfUser);
-        }
+        self.set_allowance(spender, amount, None);
+    }
 
-        self.allowances
-            .set(&generic_address(owner), &generic_address(*spender), *amount);
-        self.env().emit_event(SetAllowance {
-            owner: generic_address(owner),
-            spender: generic_address(*spender),
-            allowance: *amount,
-        });
+    /// Approves the spender to spend the given amount of tokens on behalf of the caller
+    /// until the given block time, after which the allowance counts as zero.
+    pub fn approve_with_expiry(&mut self, spender: &Address, amount: &U256, expires_at: u64) {
+        self.require_future_expiry(expires_at);
+        self.set_allowance(spender, amount, Some(expires_at));
     }
 
     /// Decreases the allowance of the spender by the given amount.
     pub fn decrease_allowance(&mut self, spender: &Address, decr_by: &U256) {
         let owner = self.env().caller();
+        self.require_not_blacklisted(&[&owner, spender]);
         let allowance = self.allowance(&owner, spender);
         self.allowances.set(
             &generic_address(owner),
             &generic_address(*spender),
             allowance.saturating_sub(*decr_by),
         );
-        self.env().emit_event(DecreaseAllowance {
-            owner: generic_address(owner),
-            spender: generic_address(*spender),
-            allowance,
-            decr_by: *decr_by,
-        });
+        emit_event(
+            &self.env(),
+            DecreaseAllowance {
+                owner,
+                spender: *spender,
+                allowance: self.allowance(&owner, spender),
+                decr_by: *decr_by,
+            },
+        );
     }
 
     /// Increases the allowance of the spender by the given amount.
+    /// The expiry of the allowance is kept, an expired allowance restarts from zero without expiry.
     pub fn increase_allowance(&mut self, spender: &Address, inc_by: &U256) {
-        let owner = self.env().caller();
-        if owner == *spender {
-            self.env().revert(Error::CannotTargetSelfUser);
-        }
-        let allowance = self
-            .allowances
-            .get_or_default(&generic_address(owner), &generic_address(*spender));
+        self.raise_allowance(spender, inc_by, None);
+    }
 
-        self.allowances.set(
-            &generic_address(owner),
-            &generic_address(*spender),
-            allowance.saturating_add(*inc_by),
-        );
-        self.env().emit_event(IncreaseAllowance {
-            owner: generic_address(owner),
-            spender: generic_address(*spender),
-            allowance,
-            inc_by: *inc_by,
-        });
+    /// Increases the allowance of the spender by the given amount and sets its expiry.
+    pub fn increase_allowance_with_expiry(
+        &mut self,
+        spender: &Address,
+        inc_by: &U256,
+        expires_at: u64,
+    ) {
+        self.require_future_expiry(expires_at);
+        self.raise_allowance(spender, inc_by, Some(expires_at));
     }
 
     /// Transfers tokens from the caller to the recipient.
     pub fn transfer(&mut self, recipient: &Address, amount: &U256) {
         let caller = self.env().caller();
+        self.require_not_blacklisted(&[&caller, recipient]);
         if caller == *recipient {
             self.env().revert(Error::CannotTargetSelfUser);
         }
         self.raw_transfer(&caller, recipient, amount);
     }
 
+    /// Transfers tokens from the caller to each recipient, either all legs succeed or none.
+    pub fn batch_transfer(&mut self, transfers: Vec<(Address, U256)>) {
+        if transfers.is_empty() {
+            self.env().revert(Error::EmptyBatch);
+        }
+        for (recipient, amount) in transfers.iter() {
+            self.transfer(recipient, amount);
+        }
+    }
+
     /// Transfers tokens from the owner to the recipient using the spender's allowance.
     pub fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256) {
-        self.require_not_role(owner, &Roles::Blacklisted);
-        self.require_not_role(recipient, &Roles::Blacklisted);
         let spender = self.env().caller();
+        self.require_not_blacklisted(&[&spender, owner, recipient]);
         if owner == recipient {
             self.env().revert(Error::CannotTargetSelfUser);
         }
@@ -206,36 +326,34 @@ impl Stablecoin {
         let allowance = self.allowance(owner, &spender);
         self.allowances.set(
             &generic_address(*owner),
-            &generic_address(*recipient),
+            &generic_address(spender),
             allowance
                 .checked_sub(*amount)
                 .unwrap_or_revert_with(&self.env(), Error::InsufficientAllowance),
         );
-        self.env().emit_event(TransferFrom {
-            spender: generic_address(spender),
-            owner: generic_address(*owner),
-            recipient: generic_address(*recipient),
-            amount: *amount,
-        });
+        emit_event(
+            &self.env(),
+            TransferFrom {
+                spender,
+                owner: *owner,
+                recipient: *recipient,
+                amount: *amount,
+            },
+        );
         self.raw_transfer(owner, recipient, amount);
     }
 
     /// Burns the given amount of tokens from the given address.
     pub fn burn(&mut self, amount: U256, account: Address) {
         self.assert_burn_and_mint_enabled();
-        self.require_not_role(&self.caller(), &Roles::Blacklisted);
+        self.require_not_blacklisted(&[&self.caller(), &account]);
         self.require_role(&self.caller(), &Roles::Minter);
         if amount == U256::zero() {
             self.env().revert(Error::InvalidAmount)
         }
-        let minter_allowance: U256 = self
-            .minter_allowances
-            .get_or_default(&generic_address(self.caller()));
-        if minter_allowance < amount {
-            self.env().revert(Error::InsufficientMinterAllowance);
+        if self.burn_consumes_minter_allowance() {
+            self.consume_minter_allowance(&self.caller(), amount);
         }
-        self.minter_allowances
-            .subtract(&generic_address(self.caller()), amount);
         // must check spender allowance
         let allowance = self.allowance(&account, &self.caller());
         if allowance < amount {
@@ -250,28 +368,119 @@ impl Stablecoin {
         );
         self.raw_burn(&account, &amount);
     }
+
+    /// Burns the given amount of tokens from the caller's own balance.
+    /// The caller must be a minter, no spender allowance is required.
+    pub fn burn_own(&mut self, amount: U256) {
+        self.assert_burn_and_mint_enabled();
+        self.require_not_blacklisted(&[&self.caller()]);
+        self.require_role(&self.caller(), &Roles::Minter);
+        if amount == U256::zero() {
+            self.env().revert(Error::InvalidAmount)
+        }
+        if amount > self.balance_of(&self.caller()) {
+            self.env().revert(Error::InsufficientBalance)
+        }
+        if self.burn_consumes_minter_allowance() {
+            self.consume_minter_allowance(&self.caller(), amount);
+        }
+        self.raw_burn(&self.caller(), &amount);
+    }
+
+    /// Configure whether burns deduct from the minter's allowance, can only be called by Owner
+    pub fn set_burn_consumes_minter_allowance(&mut self, consumes_minter_allowance: bool) {
+        self.require_role(&self.caller(), &Roles::Owner);
+        self.extensions
+            .burn_consumes_minter_allowance
+            .set(consumes_minter_allowance);
+        emit_event(
+            &self.env(),
+            BurnPolicyChanged {
+                consumes_minter_allowance,
+            },
+        );
+    }
+
     /// Mints new tokens and assigns them to the given address.
     pub fn mint(&mut self, owner: &Address, amount: U256) {
         self.require_role(&self.caller(), &Roles::Minter);
-        self.require_not_role(owner, &Roles::Blacklisted);
+        self.require_not_blacklisted(&[&self.caller(), owner]);
         self.assert_burn_and_mint_enabled();
-        let minter_allowance: U256 = self
-            .minter_allowances
-            .get_or_default(&generic_address(self.caller()));
-        if minter_allowance < amount {
-            self.env().revert(Error::InsufficientMinterAllowance);
-        }
-        self.minter_allowances
-            .subtract(&generic_address(self.caller()), amount);
+        self.consume_minter_allowance(&self.caller(), amount);
+        self.record_minted(&self.caller(), amount);
         self.raw_mint(owner, &amount);
     }
 
+    /// Mints tokens to each recipient, either all legs succeed or none.
+    pub fn batch_mint(&mut self, mints: Vec<(Address, U256)>) {
+        if mints.is_empty() {
+            self.env().revert(Error::EmptyBatch);
+        }
+        for (owner, amount) in mints.into_iter() {
+            self.mint(&owner, amount);
+        }
+    }
+
+    /// Register the contract consulted before any balance is moved, `None` removes it.
+    /// A rejection always reverts the operation. `fail_closed` only decides what happens if the
+    /// contract is unavailable: the operation reverts if it is set, otherwise the funds are moved
+    /// and a `ComplianceCheckSkipped` event is emitted. Can only be called by Owner
+    pub fn set_compliance_hook(&mut self, compliance_hook: Option<Address>, fail_closed: bool) {
+        self.require_role(&self.caller(), &Roles::Owner);
+        self.extensions.compliance_hook.set(compliance_hook);
+        self.extensions.compliance_fail_closed.set(fail_closed);
+        emit_event(
+            &self.env(),
+            ComplianceHookChanged {
+                compliance_hook: compliance_hook.map(generic_address),
+                fail_closed,
+            },
+        );
+    }
+
+    /// Set the cap on the total supply, `None` removes the cap. Can only be called by Owner
+    pub fn set_supply_cap(&mut self, supply_cap: Option<U256>) {
+        self.require_role(&self.caller(), &Roles::Owner);
+        self.extensions.supply_cap.set(supply_cap);
+        emit_event(&self.env(), SupplyCapChanged { supply_cap });
+    }
+
+    /// Take a snapshot of balances and total supply, can only be called by Owner
+    pub fn snapshot(&mut self) -> u32 {
+        self.require_role(&self.caller(), &Roles::Owner);
+        self.extensions.snapshots.take()
+    }
+
+    /// Schedule a snapshot every `interval` milliseconds of block time, `None` stops the schedule.
+    /// The schedule follows block time as the contract cannot read the block height.
+    /// Can only be called by Owner
+    pub fn set_snapshot_interval(&mut self, interval: Option<u64>) {
+        self.require_role(&self.caller(), &Roles::Owner);
+        self.extensions.snapshots.set_interval(interval);
+        emit_event(&self.env(), SnapshotIntervalChanged { interval });
+    }
+
+    /// Set the lifetime mint limit of a minter, `None` removes the limit. Can only be called by Owner
+    pub fn set_minter_lifetime_limit(&mut self, minter: &Address, limit: Option<U256>) {
+        self.require_role(&self.caller(), &Roles::Owner);
+        self.extensions
+            .minter_lifetime_limits
+            .set(&generic_address(*minter), limit);
+        emit_event(
+            &self.env(),
+            MinterLifetimeLimitChanged {
+                minter: generic_address(*minter),
+                limit,
+            },
+        );
+    }
+
     /// Pause this contract
     pub fn pause(&mut self) {
         self.require_role(&self.caller(), &Roles::Pauser);
         self.require_not_role(&self.caller(), &Roles::Blacklisted);
         self.paused.set(true);
-        self.env().emit_event(Paused {});
+        emit_event(&self.env(), Paused {});
     }
 
     /// Unpause this contract
@@ -279,7 +488,7 @@ impl Stablecoin {
         self.require_role(&self.caller(), &Roles::Pauser);
         self.require_not_role(&self.caller(), &Roles::Blacklisted);
         self.paused.set(false);
-        self.env().emit_event(Unpaused {});
+        emit_event(&self.env(), Unpaused {});
     }
 
     /// Blacklist an account
@@ -287,9 +496,15 @@ impl Stablecoin {
         self.require_role(&self.caller(), &Roles::Blacklister);
         self.roles
             .configure_role(&Roles::Blacklisted, &generic_address(*account));
-        self.env().emit_event(Blacklist {
-            account: generic_address(*account),
-        });
+        self.extensions
+            .blacklisted_accounts
+            .add(&generic_address(*account));
+        emit_event(
+            &self.env(),
+            Blacklist {
+                account: generic_address(*account),
+            },
+        );
     }
 
     /// Remove an account from the Blacklist
@@ -297,9 +512,38 @@ impl Stablecoin {
         self.require_role(&self.caller(), &Roles::Blacklister);
         self.roles
             .revoke_role(&Roles::Blacklisted, &generic_address(*account));
-        self.env().emit_event(Unblacklist {
-            account: generic_address(*account),
-        });
+        self.extensions
+            .blacklisted_accounts
+            .remove(&generic_address(*account));
+        emit_event(
+            &self.env(),
+            Unblacklist {
+                account: generic_address(*account),
+            },
+        );
+    }
+
+    /// Burn the entire balance of a blacklisted account, also while paused, can only be called by Blacklister or Owner
+    pub fn wipe_blacklisted(&mut self, account: &Address) {
+        if !self.roles.is_blacklister(&generic_address(self.caller()))
+            && !self.roles.is_owner(&generic_address(self.caller()))
+        {
+            self.env().revert(Error::InsufficientRights);
+        }
+        if !self.is_blacklisted(account) {
+            self.env().revert(Error::AccountNotBlacklisted);
+        }
+        let amount = self.balance_of(account);
+        // the compliance hook is bypassed, it would most likely reject the sanctioned account,
+        // and so is the pause, sanctioned funds must be removable during an emergency stop
+        self.remove_balance(account, &amount);
+        emit_event(
+            &self.env(),
+            BlacklistedFundsWiped {
+                account: generic_address(*account),
+                amount,
+            },
+        );
     }
 
     /// Update the Blacklister, can only be called by Owner
@@ -316,12 +560,18 @@ impl Stablecoin {
         );
         self.roles
             .configure_role(&Roles::Blacklister, &generic_address(*new_blacklister));
-        self.env().emit_event(BlacklisterChanged {
-            new_blacklister: generic_address(*new_blacklister),
-        });
-        self.env().emit_event(BlacklisterChanged {
-            new_blacklister: generic_address(*new_blacklister),
-        });
+        emit_event(
+            &self.env(),
+            BlacklisterChanged {
+                new_blacklister: generic_address(*new_blacklister),
+            },
+        );
+        emit_event(
+            &self.env(),
+            BlacklisterChanged {
+                new_blacklister: generic_address(*new_blacklister),
+            },
+        );
     }
 
     /// Configure minter allowance
@@ -331,10 +581,13 @@ impl Stablecoin {
         self.require_not_role(&minter, &Roles::Blacklisted);
         self.minter_allowances
             .set(&generic_address(minter), minter_allowance);
-        self.env().emit_event(MinterConfigured {
-            minter: generic_address(minter),
-            minter_allowance,
-        });
+        emit_event(
+            &self.env(),
+            MinterConfigured {
+                minter: generic_address(minter),
+                minter_allowance,
+            },
+        );
     }
 
     /// Increase allowance for a minter
@@ -344,10 +597,13 @@ impl Stablecoin {
         self.require_not_role(&minter, &Roles::Blacklisted);
         self.minter_allowances
             .add(&generic_address(minter), increment);
-        self.env().emit_event(MinterConfigured {
-            minter: generic_address(minter),
-            minter_allowance: self.minter_allowance(&minter),
-        });
+        emit_event(
+            &self.env(),
+            MinterConfigured {
+                minter: generic_address(minter),
+                minter_allowance: self.minter_allowance(&minter),
+            },
+        );
     }
 
     /// Decrease allowance for a minter
@@ -357,10 +613,13 @@ impl Stablecoin {
         self.require_not_role(&minter, &Roles::Blacklisted);
         self.minter_allowances
             .subtract(&generic_address(minter), decrement);
-        self.env().emit_event(MinterConfigured {
-            minter: generic_address(minter),
-            minter_allowance: self.minter_allowance(&minter),
-        });
+        emit_event(
+            &self.env(),
+            MinterConfigured {
+                minter: generic_address(minter),
+                minter_allowance: self.minter_allowance(&minter),
+            },
+        );
     }
 
     /// Add a controller, minter pair
@@ -373,10 +632,13 @@ impl Stablecoin {
         self.roles
             .configure_role(&Roles::Minter, &generic_address(*minter));
         self.controllers.set(controller, *minter);
-        self.env().emit_event(ControllerConfigured {
-            controller: generic_address(*controller),
-            minter: generic_address(*minter),
-        });
+        emit_event(
+            &self.env(),
+            ControllerConfigured {
+                controller: generic_address(*controller),
+                minter: generic_address(*minter),
+            },
+        );
     }
 
     /// Remove a controller
@@ -384,9 +646,12 @@ impl Stablecoin {
         self.require_role(&self.caller(), &Roles::MasterMinter);
         self.roles
             .revoke_role(&Roles::Controller, &generic_address(*controller));
-        self.env().emit_event(ControllerRemoved {
-            controller: generic_address(*controller),
-        });
+        emit_event(
+            &self.env(),
+            ControllerRemoved {
+                controller: generic_address(*controller),
+            },
+        );
     }
 
     /// Remove the minter role from an account
@@ -396,9 +661,12 @@ impl Stablecoin {
         let minter: Address = self.get_associated_minter(&self.env().caller());
         self.roles
             .revoke_role(&Roles::Minter, &generic_address(minter));
-        self.env().emit_event(MinterRemoved {
-            minter: generic_address(minter),
-        })
+        emit_event(
+            &self.env(),
+            MinterRemoved {
+                minter: generic_address(minter),
+            },
+        )
     }
 
     // Queries start here
@@ -413,6 +681,16 @@ impl Stablecoin {
         self.roles.is_blacklisted(&generic_address(*account))
     }
 
+    /// Query the number of blacklisted accounts
+    pub fn blacklisted_accounts_count(&self) -> u32 {
+        self.extensions.blacklisted_accounts.len()
+    }
+
+    /// Query the blacklisted account at the given index
+    pub fn blacklisted_account(&self, index: u32) -> Option<GenericAddress> {
+        self.extensions.blacklisted_accounts.get(index)
+    }
+
     /// Query the owners of this account
     pub fn is_owner(&self, account: &Address) -> bool {
         self.roles.is_owner(&generic_address(*account))
@@ -436,6 +714,104 @@ impl Stablecoin {
             .get_or_default(&generic_address(*minter))
     }
 
+    /// Query the id of the latest snapshot
+    pub fn current_snapshot_id(&self) -> u32 {
+        self.extensions.snapshots.current_id()
+    }
+
+    /// Query the block time a snapshot refers to
+    pub fn snapshot_time(&self, snapshot_id: u32) -> Option<u64> {
+        self.extensions.snapshots.time(snapshot_id)
+    }
+
+    /// Query the interval of the scheduled snapshots
+    pub fn snapshot_interval(&self) -> Option<u64> {
+        self.extensions.snapshots.interval()
+    }
+
+    /// Query the registered compliance contract
+    pub fn compliance_hook(&self) -> Option<Address> {
+        self.extensions.compliance_hook.get().flatten()
+    }
+
+    /// Query if a rejection by the compliance contract reverts the operation
+    pub fn compliance_fail_closed(&self) -> bool {
+        self.extensions.compliance_fail_closed.get_or_default()
+    }
+
+    /// Returns the version of the storage layout, zero if it predates versioning.
+    pub fn storage_version(&self) -> u32 {
+        self.storage_version.get_or_default()
+    }
+
+    /// Migrates the storage written by a previous version of the contract to the layout
+    /// of the installed code. Meant to be called once after a contract package upgrade,
+    /// can only be called by Owner. Installations that predate versioning have no Owner,
+    /// they are migrated by `init` as part of the upgrade.
+    pub fn migrate(&mut self) {
+        self.require_role(&self.caller(), &Roles::Owner);
+        self.migrate_storage();
+    }
+
+    /// Query the cap on the total supply
+    pub fn supply_cap(&self) -> Option<U256> {
+        self.extensions.supply_cap.get().flatten()
+    }
+
+    /// Query the lifetime mint limit of a minter
+    pub fn minter_lifetime_limit(&self, minter: &Address) -> Option<U256> {
+        self.extensions
+            .minter_lifetime_limits
+            .get_or_default(&generic_address(*minter))
+    }
+
+    /// Query the amount a minter has minted over its lifetime
+    pub fn minted_total(&self, minter: &Address) -> U256 {
+        self.extensions
+            .minted_totals
+            .get_or_default(&generic_address(*minter))
+    }
+
+    /// Query if burning deducts from the minter's allowance
+    pub fn burn_consumes_minter_allowance(&self) -> bool {
+        self.extensions
+            .burn_consumes_minter_allowance
+            .get()
+            .unwrap_or(true)
+    }
+
+    /// Migrates the storage to the current layout, one version at a time.
+    fn migrate_storage(&mut self) {
+        let from_version = self.storage_version();
+        if from_version >= STORAGE_VERSION {
+            self.env().revert(Error::StorageUpToDate);
+        }
+        for version in from_version..STORAGE_VERSION {
+            self.migrate_from(version);
+        }
+        self.storage_version.set(STORAGE_VERSION);
+        emit_event(
+            &self.env(),
+            StorageMigrated {
+                from_version,
+                to_version: STORAGE_VERSION,
+            },
+        );
+    }
+
+    /// Migrates the storage from the given version to the next one.
+    fn migrate_from(&mut self, version: u32) {
+        match version {
+            // installations that predate the events mode always emitted events
+            0 => {
+                if !self.events_mode.is_set() {
+                    self.events_mode.set(EventsMode::CES);
+                }
+            }
+            _ => self.env().revert(Error::InvalidState),
+        }
+    }
+
     fn require_unpaused(&self) {
         if self.paused.get().unwrap_or(false) {
             self.env().revert(Error::ContractIsPaused);
@@ -454,6 +830,27 @@ impl Stablecoin {
         }
     }
 
+    fn require_future_expiry(&self, expires_at: u64) {
+        if expires_at <= self.env().get_block_time() {
+            self.env().revert(Error::InvalidAllowanceExpiry)
+        }
+    }
+
+    fn require_valid_snapshot(&self, snapshot_id: u32) {
+        if snapshot_id == 0 || snapshot_id > self.extensions.snapshots.current_id() {
+            self.env().revert(Error::InvalidSnapshotId)
+        }
+    }
+
+    // Compliance gate for every entrypoint that moves balances or changes allowances
+    fn require_not_blacklisted(&self, accounts: &[&Address]) {
+        for account in accounts {
+            if self.roles.is_blacklisted(&generic_address(**account)) {
+                self.env().revert(Error::AccountBlacklisted)
+            }
+        }
+    }
+
     // Get the minter that is associated with the controller
     fn get_associated_minter(&mut self, controller: &Address) -> Address {
         self.controllers
@@ -467,9 +864,95 @@ impl Stablecoin {
 }
 
 impl Stablecoin {
+    /// Sets the allowance of the spender on behalf of the caller.
+    fn set_allowance(&mut self, spender: &Address, amount: &U256, expires_at: Option<u64>) {
+        self.require_unpaused();
+        self.require_not_blacklisted(&[&self.caller(), spender]);
+        let owner = self.env().caller();
+        if owner == *spender {
+            self.env().revert(Error::CannotTargetSelfUser);
+        }
+
+        self.allowances
+            .set(&generic_address(owner), &generic_address(*spender), *amount);
+        self.update_allowance_expiry(&owner, spender, expires_at);
+        emit_event(
+            &self.env(),
+            SetAllowance {
+                owner,
+                spender: *spender,
+                allowance: *amount,
+            },
+        );
+    }
+
+    /// Increases the allowance of the spender on behalf of the caller, `None` keeps the expiry.
+    fn raise_allowance(&mut self, spender: &Address, inc_by: &U256, expires_at: Option<u64>) {
+        let owner = self.env().caller();
+        self.require_not_blacklisted(&[&owner, spender]);
+        if owner == *spender {
+            self.env().revert(Error::CannotTargetSelfUser);
+        }
+        let allowance = self.allowance(&owner, spender);
+        let expires_at = match expires_at {
+            Some(expires_at) => Some(expires_at),
+            None if self.is_allowance_expired(&owner, spender) => None,
+            None => self.allowance_expiry(&owner, spender),
+        };
+
+        self.allowances.set(
+            &generic_address(owner),
+            &generic_address(*spender),
+            allowance.saturating_add(*inc_by),
+        );
+        self.update_allowance_expiry(&owner, spender, expires_at);
+        emit_event(
+            &self.env(),
+            IncreaseAllowance {
+                owner,
+                spender: *spender,
+                allowance: self.allowance(&owner, spender),
+                inc_by: *inc_by,
+            },
+        );
+    }
+
+    fn update_allowance_expiry(
+        &mut self,
+        owner: &Address,
+        spender: &Address,
+        expires_at: Option<u64>,
+    ) {
+        if self.allowance_expiry(owner, spender) == expires_at {
+            return;
+        }
+        self.allowances.set_expiry(
+            &generic_address(*owner),
+            &generic_address(*spender),
+            expires_at,
+        );
+        emit_event(
+            &self.env(),
+            AllowanceExpirySet {
+                owner: generic_address(*owner),
+                spender: generic_address(*spender),
+                expires_at,
+            },
+        );
+    }
+
+    fn is_allowance_expired(&self, owner: &Address, spender: &Address) -> bool {
+        match self.allowance_expiry(owner, spender) {
+            Some(expires_at) => self.env().get_block_time() >= expires_at,
+            None => false,
+        }
+    }
+
     /// Transfers tokens from the sender to the recipient without checking the permissions.
     fn raw_transfer(&mut self, sender: &Address, recipient: &Address, amount: &U256) {
         self.require_unpaused();
+        self.check_compliance(Some(sender), Some(recipient), amount);
+        self.extensions.snapshots.sync();
         if *amount > self.balances.get_or_default(&generic_address(*sender)) {
             self.env().revert(Error::InsufficientBalance)
         }
@@ -479,35 +962,132 @@ impl Stablecoin {
             self.balances.add(&generic_address(*recipient), *amount);
         }
 
-        self.env().emit_event(Transfer {
-            sender: generic_address(*sender),
-            recipient: generic_address(*recipient),
-            amount: *amount,
-        });
+        emit_event(
+            &self.env(),
+            Transfer {
+                sender: *sender,
+                recipient: *recipient,
+                amount: *amount,
+            },
+        );
     }
 
     /// Mints new tokens and assigns them to the given address without checking the permissions.
     fn raw_mint(&mut self, owner: &Address, amount: &U256) {
         self.require_unpaused();
+        self.check_compliance(None, Some(owner), amount);
+        self.extensions.snapshots.sync();
+        if let Some(supply_cap) = self.supply_cap() {
+            let new_total_supply = self
+                .total_supply()
+                .checked_add(*amount)
+                .unwrap_or_revert_with(&self.env(), Error::Overflow);
+            if new_total_supply > supply_cap {
+                self.env().revert(Error::SupplyCapExceeded);
+            }
+        }
         self.total_supply.add(*amount);
         self.balances.add(&generic_address(*owner), *amount);
 
-        self.env().emit_event(Mint {
-            recipient: generic_address(*owner),
-            amount: *amount,
-        });
+        emit_event(
+            &self.env(),
+            Mint {
+                recipient: *owner,
+                amount: *amount,
+            },
+        );
     }
 
     /// Burns the given amount of tokens from the given address without checking the permissions.
     fn raw_burn(&mut self, owner: &Address, amount: &U256) {
+        self.check_compliance(Some(owner), None, amount);
+        self.burn_balance(owner, amount);
+    }
+
+    /// Burns the given amount of tokens from the given address without consulting the compliance hook.
+    fn burn_balance(&mut self, owner: &Address, amount: &U256) {
         self.require_unpaused();
+        self.remove_balance(owner, amount);
+    }
+
+    /// Burns the given amount of tokens from the given address even while the contract is paused.
+    fn remove_balance(&mut self, owner: &Address, amount: &U256) {
+        self.extensions.snapshots.sync();
         self.total_supply.subtract(*amount);
         self.balances.subtract(&generic_address(*owner), *amount);
 
-        self.env().emit_event(Burn {
-            owner: generic_address(*owner),
-            amount: *amount,
-        });
+        emit_event(
+            &self.env(),
+            Burn {
+                owner: *owner,
+                amount: *amount,
+            },
+        );
+    }
+
+    /// Consults the compliance hook, if one is registered, before funds are moved.
+    fn check_compliance(
+        &mut self,
+        sender: Option<&Address>,
+        recipient: Option<&Address>,
+        amount: &U256,
+    ) {
+        let compliance_hook = match self.compliance_hook() {
+            Some(compliance_hook) => compliance_hook,
+            None => return,
+        };
+        let sender = sender.map(|sender| generic_address(*sender));
+        let recipient = recipient.map(|recipient| generic_address(*recipient));
+        // A reverting call cannot be recovered from, so the hook is only known to be unavailable
+        // if the registered address is not a contract. A reverting hook reverts the operation.
+        if !compliance_hook.is_contract() {
+            if self.compliance_fail_closed() {
+                self.env().revert(Error::ComplianceHookUnavailable);
+            }
+            emit_event(
+                &self.env(),
+                ComplianceCheckSkipped {
+                    sender,
+                    recipient,
+                    amount: *amount,
+                },
+            );
+            return;
+        }
+        // a rejection is never let through, the revert error is its only trace
+        if !ComplianceHookContractRef::new(self.env(), compliance_hook)
+            .is_compliant(sender, recipient, *amount)
+        {
+            self.env().revert(Error::ComplianceCheckFailed);
+        }
+    }
+
+    /// Deducts the given amount from the minter's allowance, reverts if it is insufficient.
+    fn consume_minter_allowance(&mut self, minter: &Address, amount: U256) {
+        let minter_allowance: U256 = self
+            .minter_allowances
+            .get_or_default(&generic_address(*minter));
+        if minter_allowance < amount {
+            self.env().revert(Error::InsufficientMinterAllowance);
+        }
+        self.minter_allowances
+            .subtract(&generic_address(*minter), amount);
+    }
+
+    /// Adds the given amount to the minter's lifetime total, reverts if it exceeds the limit.
+    fn record_minted(&mut self, minter: &Address, amount: U256) {
+        let minted_total = self
+            .minted_total(minter)
+            .checked_add(amount)
+            .unwrap_or_revert_with(&self.env(), Error::Overflow);
+        if let Some(limit) = self.minter_lifetime_limit(minter) {
+            if minted_total > limit {
+                self.env().revert(Error::MinterLifetimeLimitExceeded);
+            }
+        }
+        self.extensions
+            .minted_totals
+            .set(&generic_address(*minter), minted_total);
     }
 
     fn assert_burn_and_mint_enabled(&mut self) {
@@ -561,6 +1141,8 @@ pub(crate) mod setup_tests {
             pauser_list: vec![pauser],
             blacklister: blacklister,
             modality: Some(crate::stablecoin::utils::StablecoinModality::MintAndBurn),
+            owner: env.get_account(0),
+            events_mode: crate::stablecoin::utils::EventsMode::CES.into(),
         };
         let stablecoin = setup_with_args(&env, args);
         (
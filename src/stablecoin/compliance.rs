@@ -0,0 +1,17 @@
+//! Interface of the compliance contract a Stablecoin Owner may register.
+use crate::GenericAddress;
+use odra::casper_types::U256;
+use odra::prelude::*;
+
+/// A compliance contract is consulted before any balance is moved.
+/// `sender` is `None` for mints and `recipient` is `None` for burns.
+#[odra::external_contract]
+pub trait ComplianceHook {
+    /// Returns true if the funds may be moved.
+    fn is_compliant(
+        &self,
+        sender: Option<GenericAddress>,
+        recipient: Option<GenericAddress>,
+        amount: U256,
+    ) -> bool;
+}
@@ -0,0 +1,37 @@
+use crate::GenericAddress;
+use odra::casper_types::U256;
+use odra::prelude::*;
+use odra::{Address, Mapping};
+
+use crate::generic_address;
+
+/// A compliance contract that rejects any movement of funds from or to a denied account.
+/// Only for purpose of testing the compliance hook of the Stablecoin.
+#[odra::module]
+pub struct ComplianceMock {
+    denied: Mapping<GenericAddress, bool>,
+}
+
+#[odra::module]
+impl ComplianceMock {
+    /// Denies or allows the given account.
+    pub fn set_denied(&mut self, account: Address, denied: bool) {
+        self.denied.set(&generic_address(account), denied);
+    }
+
+    /// Returns false if the sender or the recipient is denied, the amount is not checked.
+    /// The argument keeps the name used by the ComplianceHook interface, entry point
+    /// arguments are looked up by name.
+    #[allow(unused_variables)]
+    pub fn is_compliant(
+        &self,
+        sender: Option<GenericAddress>,
+        recipient: Option<GenericAddress>,
+        amount: U256,
+    ) -> bool {
+        [sender, recipient]
+            .iter()
+            .flatten()
+            .all(|account| !self.denied.get_or_default(account))
+    }
+}
@@ -39,9 +39,6 @@ pub enum Error {
     CannotTargetSelfUser = 60017,
     /// The burn target is invalid.
     InvalidBurnTarget = 60018,
-    /// The package hash for the upgrade is missing.
-    /// This error is not used by Odra implementation yet.
-    _MissingPackageHashForUpgrade = 60019,
     /// There is no Blacklister for the Stablecoin.
     MissingBlacklister = 60020,
     /// There is no such Controller.
@@ -51,6 +48,30 @@ pub enum Error {
     InsufficientMinterAllowance = 60023,
     /// Contract is currently paused, this functionality therefore is unavailable.
     ContractIsPaused = 60024,
+    /// The account is expected to be blacklisted but is not.
+    AccountNotBlacklisted = 60025,
+    /// One of the accounts involved in the operation is blacklisted.
+    AccountBlacklisted = 60026,
+    /// Minting would push the total supply above the supply cap.
+    SupplyCapExceeded = 60027,
+    /// Minting would push the minter above its lifetime mint limit.
+    MinterLifetimeLimitExceeded = 60028,
+    /// The snapshot id is zero or has not been taken yet.
+    InvalidSnapshotId = 60029,
+    /// The interval of the scheduled snapshots is zero.
+    InvalidSnapshotInterval = 60030,
+    /// A batch operation was called without any legs.
+    EmptyBatch = 60031,
+    /// The metadata key is empty.
+    InvalidMetadataKey = 60032,
+    /// The allowance expiry is not in the future.
+    InvalidAllowanceExpiry = 60033,
+    /// The registered compliance contract rejected the operation.
+    ComplianceCheckFailed = 60034,
+    /// The registered compliance contract cannot be consulted and the hook is fail-closed.
+    ComplianceHookUnavailable = 60035,
+    /// The storage is already at the version of the installed contract.
+    StorageUpToDate = 60036,
     /// The contract is in an invalid state. This error should never happen.
     InvalidState = 60100,
     /// Trying to burn an invalid amount e.g. 0
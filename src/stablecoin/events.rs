@@ -1,13 +1,39 @@
+use crate::stablecoin::storage::events_mode;
 use crate::stablecoin::storage::Roles::Role;
+use crate::stablecoin::utils::EventsMode;
 use crate::GenericAddress;
-use odra::casper_types::U256;
+use odra::casper_event_standard::EventInstance;
+use odra::casper_types::{bytesrepr::ToBytes, U256};
 use odra::prelude::*;
+use odra::{Address, ContractEnv};
+
+/// Emits the given event. The CEP-18 token events are skipped if the contract was installed
+/// without events, administrative and compliance events are always emitted.
+pub(crate) fn emit_event<T: ToBytes + EventInstance>(env: &ContractEnv, event: T) {
+    if is_cep18_event::<T>() && events_mode(env) == EventsMode::NoEvents {
+        return;
+    }
+    env.emit_event(event);
+}
+
+fn is_cep18_event<T: EventInstance>() -> bool {
+    [
+        Mint::name(),
+        Burn::name(),
+        SetAllowance::name(),
+        IncreaseAllowance::name(),
+        DecreaseAllowance::name(),
+        Transfer::name(),
+        TransferFrom::name(),
+    ]
+    .contains(&T::name())
+}
 
 /// An event emitted when a mint operation is performed.
 #[odra::event]
 pub struct Mint {
     /// The recipient of the minted tokens.
-    pub recipient: GenericAddress,
+    pub recipient: Address,
     /// The amount of tokens minted.
     pub amount: U256,
 }
@@ -16,7 +42,7 @@ pub struct Mint {
 #[odra::event]
 pub struct Burn {
     /// The owner of the tokens that are burned.
-    pub owner: GenericAddress,
+    pub owner: Address,
     /// The amount of tokens burned.
     pub amount: U256,
 }
@@ -25,9 +51,9 @@ pub struct Burn {
 #[odra::event]
 pub struct SetAllowance {
     /// The owner of the tokens.
-    pub owner: GenericAddress,
+    pub owner: Address,
     /// The spender that is allowed to spend the tokens.
-    pub spender: GenericAddress,
+    pub spender: Address,
     /// The allowance amount.
     pub allowance: U256,
 }
@@ -36,9 +62,9 @@ pub struct SetAllowance {
 #[odra::event]
 pub struct IncreaseAllowance {
     /// The owner of the tokens.
-    pub owner: GenericAddress,
+    pub owner: Address,
     /// The spender that is allowed to spend the tokens.
-    pub spender: GenericAddress,
+    pub spender: Address,
     /// The final allowance amount.
     pub allowance: U256,
     /// The amount by which the allowance was increased.
@@ -49,22 +75,33 @@ pub struct IncreaseAllowance {
 #[odra::event]
 pub struct DecreaseAllowance {
     /// The owner of the tokens.
-    pub owner: GenericAddress,
+    pub owner: Address,
     /// The spender that is allowed to spend the tokens.
-    pub spender: GenericAddress,
+    pub spender: Address,
     /// The final allowance amount.
     pub allowance: U256,
     /// The amount by which the allowance was decreased.
     pub decr_by: U256,
 }
 
+/// An event emitted when the expiry of an allowance is set or cleared.
+#[odra::event]
+pub struct AllowanceExpirySet {
+    /// The owner of the tokens.
+    pub owner: GenericAddress,
+    /// The spender that is allowed to spend the tokens.
+    pub spender: GenericAddress,
+    /// The block time at which the allowance expires, none if it never expires.
+    pub expires_at: Option<u64>,
+}
+
 /// An event emitted when a transfer is performed.
 #[odra::event]
 pub struct Transfer {
     /// The sender of the tokens.
-    pub sender: GenericAddress,
+    pub sender: Address,
     /// The recipient of the tokens.
-    pub recipient: GenericAddress,
+    pub recipient: Address,
     /// The amount of tokens transferred.
     pub amount: U256,
 }
@@ -73,11 +110,11 @@ pub struct Transfer {
 #[odra::event]
 pub struct TransferFrom {
     /// The spender that is allowed to spend the tokens.
-    pub spender: GenericAddress,
+    pub spender: Address,
     /// The sender of the tokens.
-    pub owner: GenericAddress,
+    pub owner: Address,
     /// The recipient of the tokens.
-    pub recipient: GenericAddress,
+    pub recipient: Address,
     /// The amount of tokens transferred.
     pub amount: U256,
 }
@@ -90,12 +127,35 @@ pub struct Blacklist {
     pub account: GenericAddress,
 }
 
+#[odra::event]
+/// Emitted when the balance of a blacklisted account is burned.
+pub struct BlacklistedFundsWiped {
+    pub account: GenericAddress,
+    pub amount: U256,
+}
+
 #[odra::event]
 /// Emitted when blacklister account ID is changed
 pub struct BlacklisterChanged {
     pub new_blacklister: GenericAddress,
 }
 
+#[odra::event]
+/// Emitted when the Owner registers or removes the compliance contract.
+pub struct ComplianceHookChanged {
+    pub compliance_hook: Option<GenericAddress>,
+    pub fail_closed: bool,
+}
+
+#[odra::event]
+/// Emitted when an operation is let through without a verdict, because the compliance contract
+/// is unavailable and the hook is fail-open. `sender` is none for mints, `recipient` for burns.
+pub struct ComplianceCheckSkipped {
+    pub sender: Option<GenericAddress>,
+    pub recipient: Option<GenericAddress>,
+    pub amount: U256,
+}
+
 #[odra::event]
 /// Emitted when a controller is configured with a minter.
 pub struct ControllerConfigured {
@@ -122,6 +182,39 @@ pub struct MinterRemoved {
     pub minter: GenericAddress,
 }
 
+#[odra::event]
+/// Emitted when the name, symbol or another metadata value is changed.
+pub struct MetadataUpdated {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+#[odra::event]
+/// Emitted when the lifetime mint limit of a minter is changed.
+pub struct MinterLifetimeLimitChanged {
+    pub minter: GenericAddress,
+    pub limit: Option<U256>,
+}
+
+#[odra::event]
+/// Emitted when a snapshot of balances and total supply is taken.
+pub struct Snapshot {
+    pub id: u32,
+    pub block_time: u64,
+}
+
+#[odra::event]
+/// Emitted when the interval of the scheduled snapshots is changed.
+pub struct SnapshotIntervalChanged {
+    pub interval: Option<u64>,
+}
+
+#[odra::event]
+/// Emitted when the cap on the total supply is changed.
+pub struct SupplyCapChanged {
+    pub supply_cap: Option<U256>,
+}
+
 #[odra::event]
 /// Emitted when contract is paused.
 pub struct Paused {}
@@ -146,8 +239,21 @@ pub struct RoleRevoked {
     pub account: GenericAddress,
 }
 
+#[odra::event]
+/// Emitted when the Owner configures whether burns consume the minter allowance.
+pub struct BurnPolicyChanged {
+    pub consumes_minter_allowance: bool,
+}
+
 #[odra::event]
 /// Emitted when account ID is unblacklisted.
 pub struct Unblacklist {
     pub account: GenericAddress,
 }
+
+#[odra::event]
+/// Emitted when the storage has been migrated after a contract upgrade.
+pub struct StorageMigrated {
+    pub from_version: u32,
+    pub to_version: u32,
+}
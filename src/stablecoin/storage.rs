@@ -1,23 +1,32 @@
 use alloc::string::String;
 use odra::casper_types::U256;
+use odra::Address;
 use odra::ExecutionError::AdditionOverflow;
 
 use odra::casper_types::bytesrepr::ToBytes;
 use odra::UnwrapOrRevert;
-use odra::{prelude::*, Mapping};
+use odra::{prelude::*, ContractEnv, List, Mapping, SubModule, Var};
 
-use crate::stablecoin::errors::Error::{InvalidState, Overflow};
+use crate::stablecoin::errors::Error::{
+    InvalidSnapshotInterval, InvalidState, MissingEventsMode, Overflow,
+};
+use crate::stablecoin::utils::EventsMode;
 
 use base64::prelude::*;
 
-use super::events::{RoleConfigured, RoleRevoked};
+use super::events::{emit_event, RoleConfigured, RoleRevoked, Snapshot};
 const ALLOWANCES_KEY: &str = "allowances";
+const ALLOWANCE_EXPIRIES_KEY: &str = "allowance_expiries";
 const MINTER_ALLOWANCES_KEY: &str = "minter_allowances";
 const BALANCES_KEY: &str = "balances";
 const NAME_KEY: &str = "name";
 const DECIMALS_KEY: &str = "decimals";
 const SYMBOL_KEY: &str = "symbol";
 const TOTAL_SUPPLY_KEY: &str = "total_supply";
+const EVENTS_MODE_KEY: &str = "events_mode";
+const SNAPSHOT_ID_KEY: &str = "snapshot_id";
+const BALANCE_CHECKPOINTS_KEY: &str = "balance_checkpoints";
+const TOTAL_SUPPLY_CHECKPOINTS_KEY: &str = "total_supply_checkpoints";
 
 type GenericAddress = [u8; 32];
 
@@ -38,6 +47,12 @@ impl StablecoinNameStorage {
             .get_named_value(NAME_KEY)
             .unwrap_or_revert_with(&self.env(), InvalidState)
     }
+
+    /// Returns true if the name has been set, which every installation does in `init`.
+    pub fn is_set(&self) -> bool {
+        let name: Option<String> = self.env().get_named_value(NAME_KEY);
+        name.is_some()
+    }
 }
 
 #[odra::module]
@@ -78,6 +93,30 @@ impl StablecoinSymbolStorage {
     }
 }
 
+#[odra::module]
+/// Storage module for the events mode of the token.
+pub struct StablecoinEventsModeStorage;
+
+#[odra::module]
+impl StablecoinEventsModeStorage {
+    /// Sets the events mode of the token.
+    pub fn set(&self, events_mode: EventsMode) {
+        self.env()
+            .set_named_value(EVENTS_MODE_KEY, u8::from(events_mode));
+    }
+
+    /// Gets the events mode of the token.
+    pub fn get(&self) -> EventsMode {
+        events_mode(&self.env())
+    }
+
+    /// Returns true if the events mode has been set.
+    pub fn is_set(&self) -> bool {
+        let events_mode: Option<u8> = self.env().get_named_value(EVENTS_MODE_KEY);
+        events_mode.is_some()
+    }
+}
+
 #[odra::module]
 /// Storage module for the total supply of the token.
 pub struct StablecoinTotalSupplyStorage;
@@ -86,7 +125,12 @@ pub struct StablecoinTotalSupplyStorage;
 impl StablecoinTotalSupplyStorage {
     /// Sets the total supply of the token.
     pub fn set(&self, total_supply: U256) {
-        self.env().set_named_value(TOTAL_SUPPLY_KEY, total_supply);
+        let env = self.env();
+        let checkpoints = Checkpoints::new(&env, TOTAL_SUPPLY_CHECKPOINTS_KEY, Vec::new());
+        if checkpoints.is_due() {
+            checkpoints.record(self.get());
+        }
+        env.set_named_value(TOTAL_SUPPLY_KEY, total_supply);
     }
 
     /// Gets the total supply of the token.
@@ -113,6 +157,14 @@ impl StablecoinTotalSupplyStorage {
             .unwrap_or_revert_with(&self.env(), Overflow);
         self.set(new_total_supply);
     }
+
+    /// Gets the total supply of the token at the given snapshot.
+    pub fn get_at(&self, snapshot_id: u32) -> U256 {
+        let env = self.env();
+        Checkpoints::new(&env, TOTAL_SUPPLY_CHECKPOINTS_KEY, Vec::new())
+            .value_at(snapshot_id)
+            .unwrap_or_else(|| self.get())
+    }
 }
 
 #[odra::module]
@@ -123,8 +175,20 @@ pub struct StablecoinBalancesStorage;
 impl StablecoinBalancesStorage {
     /// Sets the balance of the given account.
     pub fn set(&self, account: &GenericAddress, balance: U256) {
-        self.env()
-            .set_dictionary_value(BALANCES_KEY, self.key(account).as_bytes(), balance);
+        let env = self.env();
+        let checkpoints = self.checkpoints(&env, account);
+        if checkpoints.is_due() {
+            checkpoints.record(self.get_or_default(account));
+        }
+        env.set_dictionary_value(BALANCES_KEY, self.key(account).as_bytes(), balance);
+    }
+
+    /// Gets the balance of the given account at the given snapshot.
+    pub fn get_at(&self, account: &GenericAddress, snapshot_id: u32) -> U256 {
+        let env = self.env();
+        self.checkpoints(&env, account)
+            .value_at(snapshot_id)
+            .unwrap_or_else(|| self.get_or_default(account))
     }
 
     /// Gets the balance of the given account.
@@ -161,6 +225,10 @@ impl StablecoinBalancesStorage {
         let preimage = owner.to_bytes().unwrap_or_revert(&self.env());
         BASE64_STANDARD.encode(preimage)
     }
+
+    fn checkpoints<'a>(&self, env: &'a ContractEnv, account: &GenericAddress) -> Checkpoints<'a> {
+        Checkpoints::new(env, BALANCE_CHECKPOINTS_KEY, account.to_vec())
+    }
 }
 
 #[odra::module]
@@ -200,6 +268,25 @@ impl StablecoinAllowancesStorage {
         self.set(owner, spender, new_allowance);
     }
 
+    /// Sets the block time at which the allowance of the given owner and spender expires.
+    pub fn set_expiry(
+        &self,
+        owner: &GenericAddress,
+        spender: &GenericAddress,
+        expiry: Option<u64>,
+    ) {
+        self.env()
+            .set_dictionary_value(ALLOWANCE_EXPIRIES_KEY, &self.key(owner, spender), expiry);
+    }
+
+    /// Gets the block time at which the allowance of the given owner and spender expires.
+    pub fn get_expiry(&self, owner: &GenericAddress, spender: &GenericAddress) -> Option<u64> {
+        let expiry: Option<Option<u64>> = self
+            .env()
+            .get_dictionary_value(ALLOWANCE_EXPIRIES_KEY, &self.key(owner, spender));
+        expiry.flatten()
+    }
+
     fn key(&self, owner: &GenericAddress, spender: &GenericAddress) -> [u8; 64] {
         let mut result = [0u8; 64];
         let mut preimage = Vec::new();
@@ -288,19 +375,25 @@ pub struct StablecoinRoles {
 impl StablecoinRoles {
     pub fn configure_role(&mut self, role: &Roles::Role, account: &GenericAddress) {
         self.roles.set(&(*role, *account), true);
-        self.env().emit_event(RoleConfigured {
-            role: *role,
-            account: *account,
-        });
+        emit_event(
+            &self.env(),
+            RoleConfigured {
+                role: *role,
+                account: *account,
+            },
+        );
     }
 
     pub fn revoke_role(&mut self, role: &Roles::Role, account: &GenericAddress) {
         if self.has_role(role, account) {
             self.roles.set(&(*role, *account), false);
-            self.env().emit_event(RoleRevoked {
-                role: *role,
-                account: *account,
-            });
+            emit_event(
+                &self.env(),
+                RoleRevoked {
+                    role: *role,
+                    account: *account,
+                },
+            );
         }
     }
 
@@ -329,3 +422,274 @@ impl StablecoinRoles {
         self.roles.get_or_default(&(*role, *account))
     }
 }
+
+#[odra::module]
+/// Storage module for the enumerable set of blacklisted accounts.
+pub struct StablecoinBlacklistedAccounts {
+    accounts: List<GenericAddress>,
+    // position of an account in the list, offset by one so that zero means absent
+    positions: Mapping<GenericAddress, u32>,
+}
+
+#[odra::module]
+impl StablecoinBlacklistedAccounts {
+    /// Adds the account to the set, does nothing if it is already present.
+    pub fn add(&mut self, account: &GenericAddress) {
+        if self.contains(account) {
+            return;
+        }
+        self.accounts.push(*account);
+        self.positions.set(account, self.accounts.len());
+    }
+
+    /// Removes the account from the set, does nothing if it is absent.
+    pub fn remove(&mut self, account: &GenericAddress) {
+        let position = self.positions.get_or_default(account);
+        if position == 0 {
+            return;
+        }
+        // swap the last account into the freed slot
+        let last = self
+            .accounts
+            .pop()
+            .unwrap_or_revert_with(&self.env(), InvalidState);
+        if last != *account {
+            self.accounts.replace(position - 1, last);
+            self.positions.set(&last, position);
+        }
+        self.positions.set(account, 0);
+    }
+
+    /// Returns true if the account is in the set.
+    pub fn contains(&self, account: &GenericAddress) -> bool {
+        self.positions.get_or_default(account) != 0
+    }
+
+    /// Returns the number of accounts in the set.
+    pub fn len(&self) -> u32 {
+        self.accounts.len()
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Returns the account at the given index.
+    pub fn get(&self, index: u32) -> Option<GenericAddress> {
+        // popped slots keep their old value, so bound the lookup by the length
+        if index >= self.accounts.len() {
+            return None;
+        }
+        self.accounts.get(index)
+    }
+}
+
+/// Returns the events mode the contract was installed with, reverts with `MissingEventsMode`
+/// for installations that predate the events mode and have not been migrated yet.
+pub(crate) fn events_mode(env: &ContractEnv) -> EventsMode {
+    let events_mode: Option<u8> = env.get_named_value(EVENTS_MODE_KEY);
+    match events_mode {
+        Some(events_mode) => EventsMode::try_from(events_mode).unwrap_or_revert(env),
+        None => env.revert(MissingEventsMode),
+    }
+}
+
+/// Returns the id of the latest snapshot, zero if no snapshot was taken yet.
+fn current_snapshot_id(env: &ContractEnv) -> u32 {
+    env.get_named_value(SNAPSHOT_ID_KEY).unwrap_or_default()
+}
+
+/// Ordered list of (snapshot id, value) pairs of a single owner, stored in a dictionary.
+/// A pair holds the value as it was when the snapshot was taken, and is written
+/// lazily on the first update after that snapshot.
+struct Checkpoints<'a> {
+    env: &'a ContractEnv,
+    dictionary: &'static str,
+    owner: Vec<u8>,
+}
+
+impl<'a> Checkpoints<'a> {
+    fn new(env: &'a ContractEnv, dictionary: &'static str, owner: Vec<u8>) -> Self {
+        Self {
+            env,
+            dictionary,
+            owner,
+        }
+    }
+
+    /// Returns true if the value has not been recorded for the latest snapshot yet.
+    fn is_due(&self) -> bool {
+        let snapshot_id = current_snapshot_id(self.env);
+        if snapshot_id == 0 {
+            return false;
+        }
+        match self.len() {
+            0 => true,
+            len => self.get(len - 1).0 < snapshot_id,
+        }
+    }
+
+    /// Records the value for the latest snapshot.
+    fn record(&self, value: U256) {
+        let len = self.len();
+        self.env.set_dictionary_value(
+            self.dictionary,
+            &self.key(Some(len)),
+            (current_snapshot_id(self.env), value),
+        );
+        self.env
+            .set_dictionary_value(self.dictionary, &self.key(None), len + 1);
+    }
+
+    /// Returns the value at the given snapshot, `None` if it did not change since.
+    fn value_at(&self, snapshot_id: u32) -> Option<U256> {
+        // find the first checkpoint taken at or after the snapshot
+        let (mut low, mut high) = (0u32, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.get(mid).0 < snapshot_id {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        if low == self.len() {
+            None
+        } else {
+            Some(self.get(low).1)
+        }
+    }
+
+    fn len(&self) -> u32 {
+        self.env
+            .get_dictionary_value(self.dictionary, &self.key(None))
+            .unwrap_or_default()
+    }
+
+    fn get(&self, index: u32) -> (u32, U256) {
+        self.env
+            .get_dictionary_value(self.dictionary, &self.key(Some(index)))
+            .unwrap_or_revert_with(self.env, InvalidState)
+    }
+
+    // `None` addresses the length of the list, `Some(index)` one of its entries
+    fn key(&self, index: Option<u32>) -> [u8; 64] {
+        let mut result = [0u8; 64];
+        let mut preimage = self.owner.clone();
+        preimage.append(&mut index.to_bytes().unwrap_or_revert(self.env));
+        let key_bytes = self.env.hash(&preimage);
+        odra::utils::hex_to_slice(&key_bytes, &mut result);
+        result
+    }
+}
+
+#[odra::module(events = [Snapshot])]
+/// Storage module for the snapshots of balances and total supply.
+pub struct StablecoinSnapshots {
+    // block time each snapshot refers to
+    times: Mapping<u32, u64>,
+    // interval of the scheduled snapshots, none if they are disabled
+    interval: Var<Option<u64>>,
+    // block time of the next scheduled snapshot
+    next_scheduled: Var<u64>,
+}
+
+#[odra::module]
+impl StablecoinSnapshots {
+    /// Takes a new snapshot and returns its id.
+    pub fn take(&mut self) -> u32 {
+        self.sync();
+        let block_time = self.env().get_block_time();
+        self.create(block_time)
+    }
+
+    /// Sets the interval of the scheduled snapshots, `None` disables them.
+    pub fn set_interval(&mut self, interval: Option<u64>) {
+        if interval == Some(0) {
+            self.env().revert(InvalidSnapshotInterval)
+        }
+        self.sync();
+        self.interval.set(interval);
+        if let Some(interval) = interval {
+            let next_scheduled = self
+                .env()
+                .get_block_time()
+                .checked_add(interval)
+                .unwrap_or_revert_with(&self.env(), Overflow);
+            self.next_scheduled.set(next_scheduled);
+        }
+    }
+
+    /// Creates the latest scheduled snapshot whose time has passed.
+    /// Scheduled snapshots are created lazily before the next balance change, so when
+    /// several intervals passed without a change only the latest one gets an id.
+    pub fn sync(&mut self) {
+        let interval = match self.interval() {
+            Some(interval) if interval > 0 => interval,
+            _ => return,
+        };
+        let next_scheduled = self.next_scheduled.get_or_default();
+        let block_time = self.env().get_block_time();
+        if block_time < next_scheduled {
+            return;
+        }
+        let scheduled = next_scheduled + (block_time - next_scheduled) / interval * interval;
+        self.create(scheduled);
+        // a schedule beyond the end of time never fires again
+        self.next_scheduled.set(scheduled.saturating_add(interval));
+    }
+
+    /// Returns the id of the latest snapshot.
+    pub fn current_id(&self) -> u32 {
+        current_snapshot_id(&self.env())
+    }
+
+    /// Returns the interval of the scheduled snapshots.
+    pub fn interval(&self) -> Option<u64> {
+        self.interval.get().flatten()
+    }
+
+    /// Returns the block time the given snapshot refers to.
+    pub fn time(&self, snapshot_id: u32) -> Option<u64> {
+        self.times.get(&snapshot_id)
+    }
+
+    fn create(&mut self, block_time: u64) -> u32 {
+        let snapshot_id = self.current_id() + 1;
+        self.env().set_named_value(SNAPSHOT_ID_KEY, snapshot_id);
+        self.times.set(&snapshot_id, block_time);
+        emit_event(
+            &self.env(),
+            Snapshot {
+                id: snapshot_id,
+                block_time,
+            },
+        );
+        snapshot_id
+    }
+}
+
+#[odra::module]
+/// Storage module for the state added after the initial release of the token. It is kept
+/// behind a single field of the token to leave the storage layout of the earlier fields
+/// unchanged, and as a module can only hold a limited number of fields.
+pub struct StablecoinExtensions {
+    pub(crate) snapshots: SubModule<StablecoinSnapshots>,
+    pub(crate) blacklisted_accounts: SubModule<StablecoinBlacklistedAccounts>,
+    // Whether burning deducts from the minter's mint allowance, defaults to true
+    pub(crate) burn_consumes_minter_allowance: Var<bool>,
+    // Hard ceiling on the total supply, no ceiling if unset
+    pub(crate) supply_cap: Var<Option<U256>>,
+    // Optional lifetime mint limit per minter
+    pub(crate) minter_lifetime_limits: Mapping<GenericAddress, Option<U256>>,
+    // Amount each minter has minted over its lifetime
+    pub(crate) minted_totals: Mapping<GenericAddress, U256>,
+    // Additional metadata such as icon URL, issuer or reserve attestation URL
+    pub(crate) metadata: Mapping<String, Option<String>>,
+    pub(crate) metadata_keys: Var<Vec<String>>,
+    // Contract consulted before any balance is moved
+    pub(crate) compliance_hook: Var<Option<Address>>,
+    // Whether a rejection by the compliance hook reverts the operation
+    pub(crate) compliance_fail_closed: Var<bool>,
+}
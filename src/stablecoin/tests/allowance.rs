@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod allowance_tests {
     use crate::stablecoin::cross_contract_test::StablecoinClientContractHostRef;
-    use crate::stablecoin::errors::Error::InsufficientAllowance;
+    use crate::stablecoin::errors::Error::{InsufficientAllowance, InvalidAllowanceExpiry};
     use crate::stablecoin::setup_tests::{
         setup, setup_with_args, ALLOWANCE_AMOUNT_1, ALLOWANCE_AMOUNT_2, TOKEN_DECIMALS, TOKEN_NAME,
         TOKEN_SYMBOL, TOKEN_TOTAL_SUPPLY, TRANSFER_AMOUNT_1,
@@ -152,6 +152,8 @@ mod allowance_tests {
             pauser_list: vec![],
             blacklister: blacklister,
             modality: Some(crate::stablecoin::utils::StablecoinModality::MintAndBurn),
+            owner: env.get_account(0),
+            events_mode: crate::stablecoin::utils::EventsMode::CES.into(),
         };
         let mut stablecoin = setup_with_args(&env, args);
         env.set_caller(master_minter);
@@ -176,6 +178,8 @@ mod allowance_tests {
             pauser_list: vec![],
             blacklister: blacklister,
             modality: Some(crate::stablecoin::utils::StablecoinModality::MintAndBurn),
+            owner: env.get_account(0),
+            events_mode: crate::stablecoin::utils::EventsMode::CES.into(),
         };
         let mut stablecoin = setup_with_args(&env, args);
         env.set_caller(master_minter);
@@ -185,4 +189,86 @@ mod allowance_tests {
         stablecoin.decrease_minter_allowance(U256::from(5));
         assert_eq!(stablecoin.minter_allowance(&minter_1), U256::from(5));
     }
+
+    #[test]
+    fn should_treat_expired_allowance_as_zero() {
+        let (env, .., mut stablecoin) = setup();
+        let owner = env.caller();
+        let alice = env.get_account(1);
+        let expires_at = env.block_time() + 1_000;
+        let result = stablecoin.try_approve_with_expiry(
+            &alice,
+            &ALLOWANCE_AMOUNT_1.into(),
+            env.block_time(),
+        );
+        assert_eq!(result.err().unwrap(), InvalidAllowanceExpiry.into());
+        stablecoin.approve_with_expiry(&alice, &ALLOWANCE_AMOUNT_1.into(), expires_at);
+        assert!(
+            env.emitted(&stablecoin, "AllowanceExpirySet"),
+            "AllowanceExpirySet event not emitted"
+        );
+        assert_eq!(
+            stablecoin.allowance_expiry(&owner, &alice),
+            Some(expires_at)
+        );
+        // increasing keeps the expiry
+        stablecoin.increase_allowance(&alice, &ALLOWANCE_AMOUNT_2.into());
+        assert_eq!(
+            stablecoin.allowance_expiry(&owner, &alice),
+            Some(expires_at)
+        );
+        assert_eq!(
+            stablecoin.allowance(&owner, &alice),
+            (ALLOWANCE_AMOUNT_1 + ALLOWANCE_AMOUNT_2).into()
+        );
+        env.set_caller(alice);
+        stablecoin.transfer_from(&owner, &alice, &TRANSFER_AMOUNT_1.into());
+        env.advance_block_time(1_000);
+        assert_eq!(stablecoin.allowance(&owner, &alice), U256::zero());
+        let result = stablecoin.try_transfer_from(&owner, &alice, &U256::one());
+        assert_eq!(result.err().unwrap(), InsufficientAllowance.into());
+        // an expired allowance restarts from zero without expiry
+        env.set_caller(owner);
+        stablecoin.increase_allowance(&alice, &ALLOWANCE_AMOUNT_2.into());
+        assert_eq!(stablecoin.allowance_expiry(&owner, &alice), None);
+        assert_eq!(
+            stablecoin.allowance(&owner, &alice),
+            ALLOWANCE_AMOUNT_2.into()
+        );
+        stablecoin.increase_allowance_with_expiry(
+            &alice,
+            &ALLOWANCE_AMOUNT_2.into(),
+            env.block_time() + 1_000,
+        );
+        assert_eq!(
+            stablecoin.allowance(&owner, &alice),
+            (ALLOWANCE_AMOUNT_2 * 2).into()
+        );
+        // approving without expiry clears it
+        stablecoin.approve(&alice, &ALLOWANCE_AMOUNT_1.into());
+        assert_eq!(stablecoin.allowance_expiry(&owner, &alice), None);
+        env.advance_block_time(2_000);
+        assert_eq!(
+            stablecoin.allowance(&owner, &alice),
+            ALLOWANCE_AMOUNT_1.into()
+        );
+    }
+
+    #[test]
+    fn should_not_burn_with_expired_allowance() {
+        let (env, master_minter, controller_1, minter_1, .., user, mut stablecoin) = setup();
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller_1, &minter_1);
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(20));
+        env.set_caller(minter_1);
+        stablecoin.mint(&user, U256::from(10));
+        env.set_caller(user);
+        stablecoin.approve_with_expiry(&minter_1, &U256::from(10), env.block_time() + 1_000);
+        env.advance_block_time(1_000);
+        env.set_caller(minter_1);
+        let result = stablecoin.try_burn(U256::from(10), user);
+        assert_eq!(result.err().unwrap(), InsufficientAllowance.into());
+        assert_eq!(stablecoin.balance_of(&user), U256::from(10));
+    }
 }
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod batch_tests {
+    use crate::stablecoin::errors::Error::{
+        AccountBlacklisted, ContractIsPaused, EmptyBatch, InsufficientBalance,
+        InsufficientMinterAllowance,
+    };
+    use crate::stablecoin::setup_tests::{setup, TOKEN_TOTAL_SUPPLY};
+    use crate::stablecoin::StablecoinHostRef;
+    use odra::casper_types::{U256, U512};
+    use odra::host::{HostEnv, HostRef};
+    use odra::{Address, DeployReport};
+
+    fn configure_minter(
+        stablecoin: &mut StablecoinHostRef,
+        master_minter: Address,
+        controller: Address,
+        minter: Address,
+        allowance: u64,
+    ) {
+        let env = stablecoin.env().clone();
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller, &minter);
+        env.set_caller(controller);
+        stablecoin.configure_minter_allowance(U256::from(allowance));
+    }
+
+    #[test]
+    fn should_batch_transfer() {
+        let (env, .., user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        let alice = env.get_account(7);
+        let events_count = env.events_count(&stablecoin);
+        stablecoin.batch_transfer(vec![(user, U256::from(10)), (alice, U256::from(20))]);
+        // one Transfer event per leg
+        assert_eq!(env.events_count(&stablecoin), events_count + 2);
+        assert!(
+            env.emitted(&stablecoin, "Transfer"),
+            "Transfer event not emitted"
+        );
+        assert_eq!(stablecoin.balance_of(&user), U256::from(10));
+        assert_eq!(stablecoin.balance_of(&alice), U256::from(20));
+        assert_eq!(
+            stablecoin.balance_of(&owner),
+            U256::from(TOKEN_TOTAL_SUPPLY - 30)
+        );
+        let result = stablecoin.try_batch_transfer(vec![]);
+        assert_eq!(result.err().unwrap(), EmptyBatch.into());
+    }
+
+    #[test]
+    fn should_revert_whole_batch_transfer_on_failing_leg() {
+        let (env, .., blacklister, pauser, user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        let alice = env.get_account(7);
+        let result = stablecoin.try_batch_transfer(vec![
+            (user, U256::from(10)),
+            (alice, U256::from(TOKEN_TOTAL_SUPPLY)),
+        ]);
+        assert_eq!(result.err().unwrap(), InsufficientBalance.into());
+        assert_eq!(stablecoin.balance_of(&user), U256::zero());
+        env.set_caller(blacklister);
+        stablecoin.blacklist(&alice);
+        env.set_caller(owner);
+        let result =
+            stablecoin.try_batch_transfer(vec![(user, U256::from(10)), (alice, U256::from(10))]);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        assert_eq!(stablecoin.balance_of(&user), U256::zero());
+        env.set_caller(pauser);
+        stablecoin.pause();
+        env.set_caller(owner);
+        let result = stablecoin.try_batch_transfer(vec![(user, U256::from(10))]);
+        assert_eq!(result.err().unwrap(), ContractIsPaused.into());
+    }
+
+    #[test]
+    fn should_batch_mint() {
+        let (env, master_minter, controller_1, minter_1, .., user, mut stablecoin) = setup();
+        let alice = env.get_account(7);
+        configure_minter(&mut stablecoin, master_minter, controller_1, minter_1, 30);
+        env.set_caller(minter_1);
+        let result =
+            stablecoin.try_batch_mint(vec![(user, U256::from(20)), (alice, U256::from(20))]);
+        assert_eq!(result.err().unwrap(), InsufficientMinterAllowance.into());
+        assert_eq!(stablecoin.minter_allowance(&minter_1), U256::from(30));
+        stablecoin.batch_mint(vec![(user, U256::from(10)), (alice, U256::from(20))]);
+        assert!(env.emitted(&stablecoin, "Mint"), "Mint event not emitted");
+        assert_eq!(stablecoin.balance_of(&user), U256::from(10));
+        assert_eq!(stablecoin.balance_of(&alice), U256::from(20));
+        assert_eq!(stablecoin.minter_allowance(&minter_1), U256::zero());
+        assert_eq!(
+            stablecoin.total_supply(),
+            U256::from(TOKEN_TOTAL_SUPPLY + 30)
+        );
+        env.set_caller(user);
+        assert!(stablecoin
+            .try_batch_mint(vec![(user, U256::from(1))])
+            .is_err());
+    }
+
+    const LEGS: u64 = 10;
+
+    fn last_call_gas(env: &HostEnv) -> U512 {
+        match env.gas_report().into_iter().last() {
+            Some(DeployReport::ContractCall { gas, .. }) => gas,
+            _ => U512::zero(),
+        }
+    }
+
+    /// Gas of each leg after the first one of a batch of `LEGS` legs.
+    fn gas_per_leg(single_leg: U512, all_legs: U512) -> U512 {
+        (all_legs - single_leg) / (LEGS - 1)
+    }
+
+    /// Checks the gas of a batch leg, `(all_legs - single_leg) / (LEGS - 1)`, against the bound
+    /// of the gas of a single-leg batch: every further leg has to cost less than a separate call,
+    /// the difference being the per-call overhead that batching saves.
+    /// Gas is only metered on the Casper backend: `cargo odra test -b casper -- --ignored`.
+    #[test]
+    #[ignore]
+    fn test_batch_gas_per_leg() {
+        let (env, master_minter, controller_1, minter_1, .., mut stablecoin) = setup();
+        let recipients: Vec<Address> = (7..7 + LEGS as usize)
+            .map(|index| env.get_account(index))
+            .collect();
+
+        stablecoin.batch_transfer(vec![(recipients[0], U256::one())]);
+        let single_leg = last_call_gas(&env);
+        stablecoin.batch_transfer(
+            recipients
+                .iter()
+                .map(|recipient| (*recipient, U256::one()))
+                .collect(),
+        );
+        let all_legs = last_call_gas(&env);
+        assert!(!single_leg.is_zero());
+        assert!(gas_per_leg(single_leg, all_legs) < single_leg);
+
+        configure_minter(
+            &mut stablecoin,
+            master_minter,
+            controller_1,
+            minter_1,
+            LEGS + 1,
+        );
+        env.set_caller(minter_1);
+        stablecoin.batch_mint(vec![(recipients[0], U256::one())]);
+        let single_leg = last_call_gas(&env);
+        stablecoin.batch_mint(
+            recipients
+                .iter()
+                .map(|recipient| (*recipient, U256::one()))
+                .collect(),
+        );
+        let all_legs = last_call_gas(&env);
+        assert!(!single_leg.is_zero());
+        assert!(gas_per_leg(single_leg, all_legs) < single_leg);
+    }
+}
@@ -0,0 +1,177 @@
+#[cfg(test)]
+mod blacklist_tests {
+    use crate::generic_address;
+    use crate::stablecoin::errors::Error::{
+        AccountBlacklisted, AccountNotBlacklisted, InsufficientRights,
+    };
+    use crate::stablecoin::setup_tests::{
+        setup, ALLOWANCE_AMOUNT_1, TOKEN_TOTAL_SUPPLY, TRANSFER_AMOUNT_1,
+    };
+    use odra::casper_types::U256;
+
+    #[test]
+    fn should_enumerate_blacklisted_accounts() {
+        let (env, .., blacklister, _, user, mut stablecoin) = setup();
+        let alice = env.get_account(7);
+        let bob = env.get_account(8);
+        env.set_caller(blacklister);
+        stablecoin.blacklist(&user);
+        stablecoin.blacklist(&alice);
+        stablecoin.blacklist(&bob);
+        // blacklisting twice does not duplicate the entry
+        stablecoin.blacklist(&alice);
+        assert_eq!(stablecoin.blacklisted_accounts_count(), 3);
+        stablecoin.unblacklist(&user);
+        assert_eq!(stablecoin.blacklisted_accounts_count(), 2);
+        let listed = [
+            stablecoin.blacklisted_account(0).unwrap(),
+            stablecoin.blacklisted_account(1).unwrap(),
+        ];
+        assert!(listed.contains(&generic_address(alice)));
+        assert!(listed.contains(&generic_address(bob)));
+        assert_eq!(stablecoin.blacklisted_account(2), None);
+    }
+
+    #[test]
+    fn should_wipe_blacklisted_balance() {
+        let (env, .., blacklister, _, user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        stablecoin.transfer(&user, &TRANSFER_AMOUNT_1.into());
+        env.set_caller(blacklister);
+        let result = stablecoin.try_wipe_blacklisted(&user);
+        assert_eq!(result.err().unwrap(), AccountNotBlacklisted.into());
+        stablecoin.blacklist(&user);
+        env.set_caller(user);
+        let result = stablecoin.try_wipe_blacklisted(&user);
+        assert_eq!(result.err().unwrap(), InsufficientRights.into());
+        env.set_caller(blacklister);
+        stablecoin.wipe_blacklisted(&user);
+        assert!(
+            env.emitted(&stablecoin, "BlacklistedFundsWiped"),
+            "BlacklistedFundsWiped event not emitted"
+        );
+        assert_eq!(stablecoin.balance_of(&user), U256::zero());
+        assert_eq!(
+            stablecoin.total_supply(),
+            (TOKEN_TOTAL_SUPPLY - TRANSFER_AMOUNT_1).into()
+        );
+        // the owner may wipe as well
+        env.set_caller(owner);
+        stablecoin.wipe_blacklisted(&user);
+    }
+
+    #[test]
+    fn should_wipe_blacklisted_balance_while_paused() {
+        let (env, .., blacklister, pauser, user, mut stablecoin) = setup();
+        stablecoin.transfer(&user, &TRANSFER_AMOUNT_1.into());
+        env.set_caller(blacklister);
+        stablecoin.blacklist(&user);
+        env.set_caller(pauser);
+        stablecoin.pause();
+        env.set_caller(blacklister);
+        stablecoin.wipe_blacklisted(&user);
+        assert_eq!(stablecoin.balance_of(&user), U256::zero());
+        assert_eq!(
+            stablecoin.total_supply(),
+            (TOKEN_TOTAL_SUPPLY - TRANSFER_AMOUNT_1).into()
+        );
+    }
+
+    #[test]
+    fn should_reject_transfers_involving_blacklisted_accounts() {
+        let (env, .., blacklister, _, user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        let alice = env.get_account(7);
+        let amount: U256 = TRANSFER_AMOUNT_1.into();
+        stablecoin.transfer(&user, &amount);
+        env.set_caller(user);
+        stablecoin.approve(&alice, &ALLOWANCE_AMOUNT_1.into());
+        env.set_caller(owner);
+        stablecoin.approve(&alice, &ALLOWANCE_AMOUNT_1.into());
+
+        // blacklisted recipient
+        env.set_caller(blacklister);
+        stablecoin.blacklist(&user);
+        env.set_caller(owner);
+        let result = stablecoin.try_transfer(&user, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        env.set_caller(alice);
+        let result = stablecoin.try_transfer_from(&owner, &user, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        // blacklisted sender
+        env.set_caller(user);
+        let result = stablecoin.try_transfer(&owner, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        env.set_caller(alice);
+        let result = stablecoin.try_transfer_from(&user, &alice, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        // blacklisted spender
+        env.set_caller(blacklister);
+        stablecoin.unblacklist(&user);
+        stablecoin.blacklist(&alice);
+        env.set_caller(alice);
+        let result = stablecoin.try_transfer_from(&owner, &user, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        assert_eq!(stablecoin.balance_of(&user), amount);
+    }
+
+    #[test]
+    fn should_reject_allowance_changes_involving_blacklisted_accounts() {
+        let (env, .., blacklister, _, user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        let amount: U256 = ALLOWANCE_AMOUNT_1.into();
+        env.set_caller(blacklister);
+        stablecoin.blacklist(&user);
+        // blacklisted spender
+        env.set_caller(owner);
+        let result = stablecoin.try_approve(&user, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        let result = stablecoin.try_increase_allowance(&user, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        let result = stablecoin.try_decrease_allowance(&user, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        // blacklisted owner
+        env.set_caller(user);
+        let result = stablecoin.try_approve(&owner, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        let result = stablecoin.try_increase_allowance(&owner, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        let result = stablecoin.try_decrease_allowance(&owner, &amount);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+    }
+
+    #[test]
+    fn should_reject_mint_and_burn_involving_blacklisted_accounts() {
+        let (env, master_minter, controller_1, minter_1, blacklister, _, user, mut stablecoin) =
+            setup();
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller_1, &minter_1);
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(100));
+        env.set_caller(minter_1);
+        stablecoin.mint(&user, U256::from(10));
+        stablecoin.mint(&minter_1, U256::from(10));
+        env.set_caller(user);
+        stablecoin.approve(&minter_1, &U256::from(10));
+
+        // blacklisted mint recipient and burn account
+        env.set_caller(blacklister);
+        stablecoin.blacklist(&user);
+        env.set_caller(minter_1);
+        let result = stablecoin.try_mint(&user, U256::from(1));
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        let result = stablecoin.try_burn(U256::from(1), user);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        // blacklisted minter
+        env.set_caller(blacklister);
+        stablecoin.unblacklist(&user);
+        stablecoin.blacklist(&minter_1);
+        env.set_caller(minter_1);
+        let result = stablecoin.try_mint(&user, U256::from(1));
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        let result = stablecoin.try_burn(U256::from(1), user);
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+        let result = stablecoin.try_burn_own(U256::from(1));
+        assert_eq!(result.err().unwrap(), AccountBlacklisted.into());
+    }
+}
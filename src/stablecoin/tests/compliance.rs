@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod compliance_tests {
+    use crate::stablecoin::compliance_mock::ComplianceMockHostRef;
+    use crate::stablecoin::errors::Error::{
+        ComplianceCheckFailed, ComplianceHookUnavailable, InsufficientRights,
+    };
+    use crate::stablecoin::setup_tests::{setup, TRANSFER_AMOUNT_1};
+    use odra::casper_types::U256;
+    use odra::host::{Deployer, HostRef, NoArgs};
+
+    #[test]
+    fn should_reject_non_compliant_operations_when_fail_closed() {
+        let (env, master_minter, controller_1, minter_1, blacklister, _, user, mut stablecoin) =
+            setup();
+        let owner = env.get_account(0);
+        let amount: U256 = TRANSFER_AMOUNT_1.into();
+        let mut compliance = ComplianceMockHostRef::deploy(&env, NoArgs);
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller_1, &minter_1);
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(10));
+        env.set_caller(user);
+        let result = stablecoin.try_set_compliance_hook(Some(*compliance.address()), true);
+        assert_eq!(result.err().unwrap(), InsufficientRights.into());
+        env.set_caller(owner);
+        stablecoin.set_compliance_hook(Some(*compliance.address()), true);
+        assert!(
+            env.emitted(&stablecoin, "ComplianceHookChanged"),
+            "ComplianceHookChanged event not emitted"
+        );
+        assert_eq!(stablecoin.compliance_hook(), Some(*compliance.address()));
+        assert!(stablecoin.compliance_fail_closed());
+        stablecoin.transfer(&user, &amount);
+
+        compliance.set_denied(user, true);
+        let result = stablecoin.try_transfer(&user, &amount);
+        assert_eq!(result.err().unwrap(), ComplianceCheckFailed.into());
+        env.set_caller(minter_1);
+        let result = stablecoin.try_mint(&user, U256::from(10));
+        assert_eq!(result.err().unwrap(), ComplianceCheckFailed.into());
+        // wiping a sanctioned balance bypasses the compliance hook
+        env.set_caller(blacklister);
+        stablecoin.blacklist(&user);
+        stablecoin.wipe_blacklisted(&user);
+        assert_eq!(stablecoin.balance_of(&user), U256::zero());
+
+        env.set_caller(owner);
+        stablecoin.set_compliance_hook(None, true);
+        assert_eq!(stablecoin.compliance_hook(), None);
+        env.set_caller(blacklister);
+        stablecoin.unblacklist(&user);
+        env.set_caller(owner);
+        stablecoin.transfer(&user, &amount);
+        assert_eq!(stablecoin.balance_of(&user), amount);
+    }
+
+    #[test]
+    fn should_reject_non_compliant_operations_when_fail_open() {
+        let (env, .., user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        let amount: U256 = TRANSFER_AMOUNT_1.into();
+        let mut compliance = ComplianceMockHostRef::deploy(&env, NoArgs);
+        compliance.set_denied(user, true);
+        stablecoin.set_compliance_hook(Some(*compliance.address()), false);
+        let result = stablecoin.try_transfer(&user, &amount);
+        assert_eq!(result.err().unwrap(), ComplianceCheckFailed.into());
+        assert_eq!(stablecoin.balance_of(&user), U256::zero());
+        assert_eq!(stablecoin.balance_of(&owner), stablecoin.total_supply());
+    }
+
+    #[test]
+    fn should_revert_when_compliance_hook_unavailable_and_fail_closed() {
+        let (env, .., user, mut stablecoin) = setup();
+        let amount: U256 = TRANSFER_AMOUNT_1.into();
+        // an account cannot answer the compliance check
+        stablecoin.set_compliance_hook(Some(env.get_account(9)), true);
+        let result = stablecoin.try_transfer(&user, &amount);
+        assert_eq!(result.err().unwrap(), ComplianceHookUnavailable.into());
+        assert_eq!(stablecoin.balance_of(&user), U256::zero());
+    }
+
+    #[test]
+    fn should_skip_compliance_check_when_hook_unavailable_and_fail_open() {
+        let (env, .., user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        let amount: U256 = TRANSFER_AMOUNT_1.into();
+        stablecoin.set_compliance_hook(Some(env.get_account(9)), false);
+        stablecoin.transfer(&user, &amount);
+        assert!(
+            env.emitted(&stablecoin, "ComplianceCheckSkipped"),
+            "ComplianceCheckSkipped event not emitted"
+        );
+        assert_eq!(stablecoin.balance_of(&user), amount);
+        assert_eq!(
+            stablecoin.balance_of(&owner) + amount,
+            stablecoin.total_supply()
+        );
+    }
+}
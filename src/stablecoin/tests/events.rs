@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod events_tests {
+    use crate::stablecoin::errors::Error::InvalidEventsMode;
+    use crate::stablecoin::events::{Mint, Transfer};
+    use crate::stablecoin::setup_tests::{
+        setup, setup_with_args, TOKEN_DECIMALS, TOKEN_NAME, TOKEN_SYMBOL, TOKEN_TOTAL_SUPPLY,
+        TRANSFER_AMOUNT_1,
+    };
+    use crate::stablecoin::utils::{EventsMode, StablecoinModality};
+    use crate::stablecoin::{StablecoinHostRef, StablecoinInitArgs};
+    use odra::host::{Deployer, HostEnv};
+    use odra::Address;
+
+    fn init_args(env: &HostEnv, events_mode: u8) -> StablecoinInitArgs {
+        StablecoinInitArgs {
+            symbol: TOKEN_SYMBOL.to_string(),
+            name: TOKEN_NAME.to_string(),
+            decimals: TOKEN_DECIMALS,
+            initial_supply: TOKEN_TOTAL_SUPPLY.into(),
+            master_minter_list: vec![env.get_account(1)],
+            pauser_list: vec![env.get_account(5)],
+            blacklister: env.get_account(4),
+            modality: Some(StablecoinModality::MintAndBurn),
+            owner: env.get_account(0),
+            events_mode,
+        }
+    }
+
+    #[test]
+    fn should_emit_ces_events_with_key_payloads() {
+        let (env, .., user, mut stablecoin) = setup();
+        let owner: Address = env.get_account(0);
+        // the owner role is granted before the initial supply is minted
+        assert_eq!(
+            env.get_event(&stablecoin, 1),
+            Ok(Mint {
+                recipient: owner,
+                amount: TOKEN_TOTAL_SUPPLY.into(),
+            })
+        );
+        stablecoin.transfer(&user, &TRANSFER_AMOUNT_1.into());
+        assert_eq!(
+            env.get_event(&stablecoin, -1),
+            Ok(Transfer {
+                sender: owner,
+                recipient: user,
+                amount: TRANSFER_AMOUNT_1.into(),
+            })
+        );
+    }
+
+    #[test]
+    fn should_not_emit_token_events_without_events_mode() {
+        let env = odra_test::env();
+        let user = env.get_account(6);
+        let blacklister = env.get_account(4);
+        let mut stablecoin = setup_with_args(&env, init_args(&env, EventsMode::NoEvents.into()));
+        stablecoin.transfer(&user, &TRANSFER_AMOUNT_1.into());
+        assert_eq!(stablecoin.balance_of(&user), TRANSFER_AMOUNT_1.into());
+        assert!(!env.emitted(&stablecoin, "Mint"));
+        assert!(!env.emitted(&stablecoin, "Transfer"));
+
+        // administrative events are emitted regardless of the events mode
+        env.set_caller(blacklister);
+        stablecoin.blacklist(&user);
+        stablecoin.wipe_blacklisted(&user);
+        assert!(
+            env.emitted(&stablecoin, "BlacklistedFundsWiped"),
+            "BlacklistedFundsWiped event not emitted"
+        );
+        assert!(!env.emitted(&stablecoin, "Burn"));
+    }
+
+    #[test]
+    fn should_not_install_with_invalid_events_mode() {
+        let env = odra_test::env();
+        let result = StablecoinHostRef::try_deploy(&env, init_args(&env, 2));
+        assert_eq!(result.err().unwrap(), InvalidEventsMode.into());
+    }
+}
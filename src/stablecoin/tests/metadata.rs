@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod metadata_tests {
+    use crate::stablecoin::errors::Error::{InsufficientRights, InvalidMetadataKey};
+    use crate::stablecoin::setup_tests::setup;
+
+    #[test]
+    fn should_update_name_and_symbol() {
+        let (env, .., user, mut stablecoin) = setup();
+        env.set_caller(user);
+        let result = stablecoin.try_set_name("EURCoin".to_string());
+        assert_eq!(result.err().unwrap(), InsufficientRights.into());
+        let result = stablecoin.try_set_symbol("EURC".to_string());
+        assert_eq!(result.err().unwrap(), InsufficientRights.into());
+        env.set_caller(env.get_account(0));
+        stablecoin.set_name("EURCoin".to_string());
+        stablecoin.set_symbol("EURC".to_string());
+        assert!(
+            env.emitted(&stablecoin, "MetadataUpdated"),
+            "MetadataUpdated event not emitted"
+        );
+        assert_eq!(stablecoin.name(), "EURCoin");
+        assert_eq!(stablecoin.symbol(), "EURC");
+    }
+
+    #[test]
+    fn should_set_and_remove_metadata() {
+        let (env, .., user, mut stablecoin) = setup();
+        let icon_url = "https://example.com/usdc.svg".to_string();
+        env.set_caller(user);
+        let result = stablecoin.try_set_metadata("icon_url".to_string(), Some(icon_url.clone()));
+        assert_eq!(result.err().unwrap(), InsufficientRights.into());
+        env.set_caller(env.get_account(0));
+        let result = stablecoin.try_set_metadata(String::new(), Some(icon_url.clone()));
+        assert_eq!(result.err().unwrap(), InvalidMetadataKey.into());
+        stablecoin.set_metadata("icon_url".to_string(), Some(icon_url.clone()));
+        stablecoin.set_metadata("issuer".to_string(), Some("Circle".to_string()));
+        stablecoin.set_metadata("issuer".to_string(), Some("Casper".to_string()));
+        assert!(
+            env.emitted(&stablecoin, "MetadataUpdated"),
+            "MetadataUpdated event not emitted"
+        );
+        assert_eq!(stablecoin.metadata("icon_url".to_string()), Some(icon_url));
+        assert_eq!(
+            stablecoin.metadata("issuer".to_string()),
+            Some("Casper".to_string())
+        );
+        assert_eq!(
+            stablecoin.metadata_keys(),
+            vec!["icon_url".to_string(), "issuer".to_string()]
+        );
+        stablecoin.set_metadata("icon_url".to_string(), None);
+        assert_eq!(stablecoin.metadata("icon_url".to_string()), None);
+        assert_eq!(stablecoin.metadata_keys(), vec!["issuer".to_string()]);
+    }
+}
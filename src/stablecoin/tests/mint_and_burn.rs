@@ -1,6 +1,10 @@
 #[cfg(test)]
 mod mint_and_burn_tests {
-    use crate::stablecoin::setup_tests::setup;
+    use crate::stablecoin::errors::Error::{
+        InsufficientBalance, InsufficientMinterAllowance, InsufficientRights,
+        MinterLifetimeLimitExceeded, SupplyCapExceeded,
+    };
+    use crate::stablecoin::setup_tests::{setup, TOKEN_TOTAL_SUPPLY};
     use odra::casper_types::U256;
     use odra::host::HostRef;
 
@@ -16,4 +20,121 @@ mod mint_and_burn_tests {
         assert_eq!(cep18_token.minter_allowance(&minter_1), U256::from(0));
         assert!(env.emitted(&cep18_token, "Mint"), "Mint event not emitted")
     }
+
+    #[test]
+    fn test_stablecoin_burn_own() {
+        let (env, master_minter, controller_1, minter_1, .., mut stablecoin) = setup();
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller_1, &minter_1);
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(15));
+        env.set_caller(minter_1);
+        stablecoin.mint(&minter_1, U256::from(10));
+        let total_supply = stablecoin.total_supply();
+        // no spender allowance is needed to burn the own balance
+        stablecoin.burn_own(U256::from(4));
+        assert!(env.emitted(&stablecoin, "Burn"), "Burn event not emitted");
+        assert_eq!(stablecoin.balance_of(&minter_1), U256::from(6));
+        assert_eq!(stablecoin.total_supply(), total_supply - 4);
+        assert_eq!(stablecoin.minter_allowance(&minter_1), U256::from(1));
+        let result = stablecoin.try_burn_own(U256::from(7));
+        assert_eq!(result.err().unwrap(), InsufficientBalance.into());
+        let result = stablecoin.try_burn_own(U256::from(2));
+        assert_eq!(result.err().unwrap(), InsufficientMinterAllowance.into());
+    }
+
+    #[test]
+    fn test_stablecoin_burn_without_consuming_minter_allowance() {
+        let (env, master_minter, controller_1, minter_1, .., user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller_1, &minter_1);
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(10));
+        env.set_caller(minter_1);
+        stablecoin.mint(&minter_1, U256::from(10));
+        assert!(stablecoin.burn_consumes_minter_allowance());
+        // only the owner may change the burn policy
+        assert!(stablecoin
+            .try_set_burn_consumes_minter_allowance(false)
+            .is_err());
+        env.set_caller(owner);
+        stablecoin.set_burn_consumes_minter_allowance(false);
+        assert!(
+            env.emitted(&stablecoin, "BurnPolicyChanged"),
+            "BurnPolicyChanged event not emitted"
+        );
+        assert!(!stablecoin.burn_consumes_minter_allowance());
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(5));
+        env.set_caller(minter_1);
+        stablecoin.burn_own(U256::from(10));
+        assert_eq!(stablecoin.balance_of(&minter_1), U256::from(0));
+        assert_eq!(stablecoin.minter_allowance(&minter_1), U256::from(5));
+        env.set_caller(user);
+        assert!(stablecoin.try_burn_own(U256::from(1)).is_err());
+    }
+
+    #[test]
+    fn test_stablecoin_supply_cap() {
+        let (env, master_minter, controller_1, minter_1, .., user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller_1, &minter_1);
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(100));
+        env.set_caller(minter_1);
+        let result = stablecoin.try_set_supply_cap(Some(U256::from(TOKEN_TOTAL_SUPPLY)));
+        assert_eq!(result.err().unwrap(), InsufficientRights.into());
+        env.set_caller(owner);
+        assert_eq!(stablecoin.supply_cap(), None);
+        stablecoin.set_supply_cap(Some(U256::from(TOKEN_TOTAL_SUPPLY + 10)));
+        assert!(
+            env.emitted(&stablecoin, "SupplyCapChanged"),
+            "SupplyCapChanged event not emitted"
+        );
+        env.set_caller(minter_1);
+        stablecoin.mint(&user, U256::from(10));
+        let result = stablecoin.try_mint(&user, U256::from(1));
+        assert_eq!(result.err().unwrap(), SupplyCapExceeded.into());
+        env.set_caller(owner);
+        stablecoin.set_supply_cap(None);
+        env.set_caller(minter_1);
+        stablecoin.mint(&user, U256::from(1));
+        assert_eq!(
+            stablecoin.total_supply(),
+            U256::from(TOKEN_TOTAL_SUPPLY + 11)
+        );
+    }
+
+    #[test]
+    fn test_stablecoin_minter_lifetime_limit() {
+        let (env, master_minter, controller_1, minter_1, .., user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller_1, &minter_1);
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(100));
+        env.set_caller(owner);
+        stablecoin.set_minter_lifetime_limit(&minter_1, Some(U256::from(15)));
+        assert!(
+            env.emitted(&stablecoin, "MinterLifetimeLimitChanged"),
+            "MinterLifetimeLimitChanged event not emitted"
+        );
+        assert_eq!(
+            stablecoin.minter_lifetime_limit(&minter_1),
+            Some(U256::from(15))
+        );
+        env.set_caller(minter_1);
+        stablecoin.mint(&user, U256::from(10));
+        assert_eq!(stablecoin.minted_total(&minter_1), U256::from(10));
+        // refilling the allowance does not reset the lifetime total
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(100));
+        env.set_caller(minter_1);
+        let result = stablecoin.try_mint(&user, U256::from(6));
+        assert_eq!(result.err().unwrap(), MinterLifetimeLimitExceeded.into());
+        stablecoin.mint(&user, U256::from(5));
+        assert_eq!(stablecoin.minted_total(&minter_1), U256::from(15));
+    }
 }
@@ -1,4 +1,11 @@
 mod allowance;
+mod batch;
+mod blacklist;
+mod compliance;
+mod events;
+mod metadata;
 mod mint_and_burn;
 mod permissions;
+mod snapshots;
 mod transfer;
+mod upgrade;
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod snapshot_tests {
+    use crate::stablecoin::errors::Error::{
+        InsufficientRights, InvalidSnapshotId, InvalidSnapshotInterval, Overflow,
+    };
+    use crate::stablecoin::setup_tests::{setup, TOKEN_TOTAL_SUPPLY, TRANSFER_AMOUNT_1};
+    use odra::casper_types::U256;
+
+    #[test]
+    fn should_query_balances_at_snapshot() {
+        let (env, master_minter, controller_1, minter_1, .., user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        let amount: U256 = TRANSFER_AMOUNT_1.into();
+        let result = stablecoin.try_balance_of_at(&owner, 1);
+        assert_eq!(result.err().unwrap(), InvalidSnapshotId.into());
+        env.set_caller(user);
+        let result = stablecoin.try_snapshot();
+        assert_eq!(result.err().unwrap(), InsufficientRights.into());
+        env.set_caller(owner);
+        let first = stablecoin.snapshot();
+        assert!(
+            env.emitted(&stablecoin, "Snapshot"),
+            "Snapshot event not emitted"
+        );
+        stablecoin.transfer(&user, &amount);
+        let second = stablecoin.snapshot();
+        stablecoin.transfer(&user, &amount);
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller_1, &minter_1);
+        env.set_caller(controller_1);
+        stablecoin.configure_minter_allowance(U256::from(10));
+        env.set_caller(minter_1);
+        stablecoin.mint(&user, U256::from(10));
+
+        assert_eq!(stablecoin.current_snapshot_id(), second);
+        assert_eq!(
+            stablecoin.balance_of_at(&owner, first),
+            TOKEN_TOTAL_SUPPLY.into()
+        );
+        assert_eq!(stablecoin.balance_of_at(&user, first), U256::zero());
+        assert_eq!(stablecoin.balance_of_at(&user, second), amount);
+        assert_eq!(stablecoin.balance_of(&user), amount * 2 + 10);
+        assert_eq!(stablecoin.total_supply_at(first), TOKEN_TOTAL_SUPPLY.into());
+        assert_eq!(
+            stablecoin.total_supply_at(second),
+            TOKEN_TOTAL_SUPPLY.into()
+        );
+        assert_eq!(
+            stablecoin.total_supply(),
+            U256::from(TOKEN_TOTAL_SUPPLY + 10)
+        );
+        let result = stablecoin.try_total_supply_at(second + 1);
+        assert_eq!(result.err().unwrap(), InvalidSnapshotId.into());
+    }
+
+    #[test]
+    fn should_take_scheduled_snapshots() {
+        let (env, .., user, mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        let amount: U256 = TRANSFER_AMOUNT_1.into();
+        let result = stablecoin.try_set_snapshot_interval(Some(0));
+        assert_eq!(result.err().unwrap(), InvalidSnapshotInterval.into());
+        env.advance_block_time(1);
+        let result = stablecoin.try_set_snapshot_interval(Some(u64::MAX));
+        assert_eq!(result.err().unwrap(), Overflow.into());
+        stablecoin.set_snapshot_interval(Some(1_000));
+        assert_eq!(stablecoin.snapshot_interval(), Some(1_000));
+        stablecoin.transfer(&user, &amount);
+        assert_eq!(stablecoin.current_snapshot_id(), 0);
+        // several intervals without a balance change share a single snapshot
+        env.advance_block_time(3_500);
+        stablecoin.transfer(&user, &amount);
+        assert_eq!(stablecoin.current_snapshot_id(), 1);
+        assert!(stablecoin.snapshot_time(1).is_some());
+        assert_eq!(stablecoin.balance_of_at(&user, 1), amount);
+        env.advance_block_time(1_000);
+        stablecoin.transfer(&user, &amount);
+        assert_eq!(stablecoin.current_snapshot_id(), 2);
+        assert_eq!(stablecoin.balance_of_at(&user, 2), amount * 2);
+        stablecoin.set_snapshot_interval(None);
+        env.advance_block_time(1_000);
+        stablecoin.transfer(&user, &amount);
+        assert_eq!(stablecoin.current_snapshot_id(), 2);
+        assert_eq!(
+            stablecoin.balance_of_at(&owner, 1),
+            U256::from(TOKEN_TOTAL_SUPPLY) - amount
+        );
+    }
+}
@@ -68,6 +68,25 @@ mod transfer_tests {
         );
     }
 
+    #[test]
+    fn should_reduce_spender_allowance_on_transfer_from_to_third_account() {
+        let (env, .., mut stablecoin) = setup();
+        let owner = env.get_account(0);
+        let alice = env.get_account(1);
+        let bob = env.get_account(2);
+        let transfer_amount = TRANSFER_AMOUNT_1.into();
+        let allowance_amount = ALLOWANCE_AMOUNT_1.into();
+        stablecoin.approve(&alice, &allowance_amount);
+        env.set_caller(alice);
+        stablecoin.transfer_from(&owner, &bob, &transfer_amount);
+        assert_eq!(stablecoin.balance_of(&bob), transfer_amount);
+        assert_eq!(
+            stablecoin.allowance(&owner, &alice),
+            allowance_amount - transfer_amount
+        );
+        assert_eq!(stablecoin.allowance(&owner, &bob), U256::zero());
+    }
+
     #[test]
     fn should_transfer_from_account_by_contract() {
         let (env, .., mut stablecoin) = setup();
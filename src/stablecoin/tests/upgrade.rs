@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod upgrade_tests {
+    use odra::casper_types::U256;
+    use odra::host::HostRef;
+    use odra::{Address, Mapping, SubModule, Var};
+
+    use crate::generic_address;
+    use crate::stablecoin::errors::Error::{
+        AlreadyInitialized, InsufficientRights, MissingEventsMode, StorageUpToDate,
+    };
+    use crate::stablecoin::setup_tests::{
+        setup, TOKEN_DECIMALS, TOKEN_NAME, TOKEN_SYMBOL, TOKEN_TOTAL_SUPPLY,
+    };
+    use crate::stablecoin::storage::Roles;
+    use crate::stablecoin::storage::{
+        StablecoinAllowancesStorage, StablecoinBalancesStorage, StablecoinDecimalsStorage,
+        StablecoinMinterAllowancesStorage, StablecoinNameStorage, StablecoinRoles,
+        StablecoinSymbolStorage, StablecoinTotalSupplyStorage,
+    };
+    use crate::stablecoin::utils::{EventsMode, StablecoinModality};
+    use crate::stablecoin::{Stablecoin, StablecoinInitArgs, STORAGE_VERSION};
+    use crate::tests::upgrade::{deploy_legacy, upgrade, upgrade_with_init};
+
+    /// Storage layout of the token before the storage was versioned.
+    #[odra::module]
+    #[allow(dead_code)]
+    pub struct LegacyStablecoin {
+        decimals: SubModule<StablecoinDecimalsStorage>,
+        symbol: SubModule<StablecoinSymbolStorage>,
+        name: SubModule<StablecoinNameStorage>,
+        total_supply: SubModule<StablecoinTotalSupplyStorage>,
+        balances: SubModule<StablecoinBalancesStorage>,
+        allowances: SubModule<StablecoinAllowancesStorage>,
+        minter_allowances: SubModule<StablecoinMinterAllowancesStorage>,
+        roles: SubModule<StablecoinRoles>,
+        controllers: Mapping<Address, Address>,
+        blacklister: Var<Address>,
+        paused: Var<bool>,
+        modality: Var<StablecoinModality>,
+    }
+
+    #[odra::module]
+    impl LegacyStablecoin {
+        pub fn init(&mut self, master_minter: Address, pauser: Address) {
+            let caller = self.env().caller();
+            self.symbol.set(TOKEN_SYMBOL.to_string());
+            self.name.set(TOKEN_NAME.to_string());
+            self.decimals.set(TOKEN_DECIMALS);
+            self.total_supply.set(TOKEN_TOTAL_SUPPLY.into());
+            self.balances
+                .set(&generic_address(caller), TOKEN_TOTAL_SUPPLY.into());
+            self.roles
+                .configure_role(&Roles::MasterMinter, &generic_address(master_minter));
+            self.roles
+                .configure_role(&Roles::Pauser, &generic_address(pauser));
+            self.modality.set(StablecoinModality::MintAndBurn);
+        }
+    }
+
+    /// Arguments of the upgrade's `init`, only the owner is used for an installed token.
+    fn init_args(owner: Address) -> StablecoinInitArgs {
+        StablecoinInitArgs {
+            symbol: String::new(),
+            name: String::new(),
+            decimals: 0,
+            initial_supply: U256::zero(),
+            master_minter_list: vec![],
+            pauser_list: vec![],
+            blacklister: owner,
+            modality: None,
+            owner,
+            events_mode: EventsMode::NoEvents.into(),
+        }
+    }
+
+    #[test]
+    fn should_install_with_current_storage_version() {
+        let (.., stablecoin) = setup();
+        assert_eq!(stablecoin.storage_version(), STORAGE_VERSION);
+    }
+
+    #[test]
+    fn should_not_migrate_up_to_date_storage() {
+        let (env, .., user, mut stablecoin) = setup();
+        env.set_caller(user);
+        let result = stablecoin.try_migrate();
+        assert_eq!(result.err().unwrap(), InsufficientRights.into());
+        env.set_caller(env.get_account(0));
+        let result = stablecoin.try_migrate();
+        assert_eq!(result.err().unwrap(), StorageUpToDate.into());
+    }
+
+    #[test]
+    fn should_migrate_unversioned_storage() {
+        let env = odra_test::env();
+        let holder = env.get_account(0);
+        let master_minter = env.get_account(1);
+        let controller = env.get_account(2);
+        let minter = env.get_account(3);
+        let pauser = env.get_account(4);
+        let owner = env.get_account(5);
+        let address = deploy_legacy::<LegacyStablecoin, Stablecoin>(
+            &env,
+            LegacyStablecoinInitArgs {
+                master_minter,
+                pauser,
+            },
+        );
+
+        // the code is upgraded, the unversioned storage has no Owner who could migrate it
+        let mut stablecoin = upgrade::<Stablecoin>(&env, address);
+        assert_eq!(stablecoin.storage_version(), 0);
+        env.set_caller(master_minter);
+        let result = stablecoin.try_migrate();
+        assert_eq!(result.err().unwrap(), InsufficientRights.into());
+        // token events cannot be emitted before the events mode is migrated
+        env.set_caller(holder);
+        let result = stablecoin.try_transfer(&owner, &1.into());
+        assert_eq!(result.err().unwrap(), MissingEventsMode.into());
+
+        // the upgrader initializes the new version, which grants the Owner and migrates
+        let mut stablecoin = upgrade_with_init::<Stablecoin>(&env, address, init_args(owner))
+            .ok()
+            .unwrap();
+        assert_eq!(stablecoin.storage_version(), STORAGE_VERSION);
+        assert!(stablecoin.is_owner(&owner));
+        assert!(!stablecoin.is_owner(&holder));
+        assert!(!stablecoin.is_owner(&master_minter));
+        let result = upgrade_with_init::<Stablecoin>(&env, address, init_args(holder));
+        assert_eq!(result.err().unwrap(), AlreadyInitialized.into());
+        env.set_caller(owner);
+        let result = stablecoin.try_migrate();
+        assert_eq!(result.err().unwrap(), StorageUpToDate.into());
+
+        // the state written before the upgrade is kept, not initialized again
+        assert_eq!(stablecoin.name(), TOKEN_NAME);
+        assert_eq!(stablecoin.symbol(), TOKEN_SYMBOL);
+        assert_eq!(stablecoin.decimals(), TOKEN_DECIMALS);
+        assert_eq!(stablecoin.total_supply(), TOKEN_TOTAL_SUPPLY.into());
+        assert_eq!(stablecoin.balance_of(&holder), TOKEN_TOTAL_SUPPLY.into());
+        assert!(stablecoin.is_pausers(&pauser));
+
+        // the migrated token keeps working with the state written before the upgrade
+        stablecoin.set_supply_cap(Some(U256::from(TOKEN_TOTAL_SUPPLY) * 2));
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller, &minter);
+        env.set_caller(controller);
+        stablecoin.configure_minter_allowance(10.into());
+        env.set_caller(minter);
+        stablecoin.mint(&minter, 10.into());
+        assert_eq!(
+            stablecoin.total_supply(),
+            U256::from(TOKEN_TOTAL_SUPPLY) + 10
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::stablecoin::errors::Error;
+
 /// Modality of the CEP-18 contract.
 #[derive(Default)]
 #[odra::odra_type]
@@ -22,3 +24,31 @@ impl From<StablecoinModality> for u8 {
         modality as u8
     }
 }
+
+/// Events mode of the CEP-18 contract.
+#[odra::odra_type]
+pub enum EventsMode {
+    /// No events are emitted.
+    NoEvents = 0,
+    /// Events are emitted following the Casper Event Standard.
+    CES = 1,
+}
+
+impl TryFrom<u8> for EventsMode {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(EventsMode::NoEvents),
+            1 => Ok(EventsMode::CES),
+            _ => Err(Error::InvalidEventsMode),
+        }
+    }
+}
+
+// implement conversion from events mode into u8
+impl From<EventsMode> for u8 {
+    fn from(events_mode: EventsMode) -> u8 {
+        events_mode as u8
+    }
+}
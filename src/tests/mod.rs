@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod signature;
 #[cfg(test)]
+pub(crate) mod upgrade;
+#[cfg(test)]
 mod test_setup {
     use super::signature::{construct_keypair, sign_message, recover_ethereum_address};
     use crate::message_transmitter::{MessageTransmitterHostRef, MessageTransmitterInitArgs};
@@ -9,7 +11,7 @@ mod test_setup {
     use crate::token_messenger_minter::{
         TokenMessengerMinterHostRef, TokenMessengerMinterInitArgs,
     };
-    use crate::{generic_address, generic_address_to_contract_address};
+    use crate::{generic_address, generic_address_to_contract_address, EthAddress};
     use crate::{
         message_transmitter::message::Message, token_messenger_minter::burn_message::BurnMessage,
     };
@@ -46,6 +48,8 @@ mod test_setup {
             pauser_list: vec![],
             blacklister,
             modality: Some(crate::stablecoin::utils::StablecoinModality::MintAndBurn),
+            owner,
+            events_mode: crate::stablecoin::utils::EventsMode::CES.into(),
         };
         let stablecoin: StablecoinHostRef = StablecoinHostRef::deploy(&env, stablecoin_init_args);
 
@@ -56,18 +60,23 @@ mod test_setup {
             next_available_nonce: 0u64,
             signature_threshold,
             owner,
+            pauser_list: vec![],
         };
-        let message_transmitter: MessageTransmitterHostRef =
+        let mut message_transmitter: MessageTransmitterHostRef =
             MessageTransmitterHostRef::deploy(&env, message_transmitter_init_args);
+        // the remote domain the tests send to
+        message_transmitter.add_remote_domain(0);
 
         let token_messenger_minter_init_args = TokenMessengerMinterInitArgs {
             version: 2u32,
             local_message_transmitter: *message_transmitter.address(),
             max_burn_amount_per_message: U256::from(100),
             owner,
+            pauser_list: vec![],
         };
         let token_messenger_minter: TokenMessengerMinterHostRef =
             TokenMessengerMinterHostRef::deploy(&env, token_messenger_minter_init_args);
+        message_transmitter.set_message_recipient(*token_messenger_minter.address(), true);
 
         (
             env,
@@ -93,6 +102,114 @@ mod test_setup {
     ) {
         setup_cctp_contracts_with_signature_threshold(0u32)
     }
+    // Contracts and accounts returned by the test setup.
+    struct CctpContracts {
+        env: HostEnv,
+        stablecoin: StablecoinHostRef,
+        message_transmitter: MessageTransmitterHostRef,
+        token_messenger_minter: TokenMessengerMinterHostRef,
+        owner: Address,
+        blacklister: Address,
+    }
+    // Sets up the contracts with the remote route of `configure_remote_route` configured.
+    fn setup_cctp_contracts_with_remote_route(signature_threshold: u32) -> CctpContracts {
+        let (
+            env,
+            mut stablecoin,
+            message_transmitter,
+            mut token_messenger_minter,
+            owner,
+            master_minter,
+            blacklister,
+            controller,
+        ) = setup_cctp_contracts_with_signature_threshold(signature_threshold);
+        configure_remote_route(
+            &env,
+            &mut stablecoin,
+            &mut token_messenger_minter,
+            owner,
+            master_minter,
+            controller,
+        );
+        CctpContracts {
+            env,
+            stablecoin,
+            message_transmitter,
+            token_messenger_minter,
+            owner,
+            blacklister,
+        }
+    }
+    // Links the remote token of domain 0 to the stablecoin and lets the TokenMessengerMinter mint it.
+    fn configure_remote_route(
+        env: &HostEnv,
+        stablecoin: &mut StablecoinHostRef,
+        token_messenger_minter: &mut TokenMessengerMinterHostRef,
+        owner: Address,
+        master_minter: Address,
+        controller: Address,
+    ) {
+        env.set_caller(master_minter);
+        stablecoin.configure_controller(&controller, token_messenger_minter.address());
+        env.set_caller(controller);
+        stablecoin.configure_minter_allowance(100.into());
+        env.set_caller(owner);
+        token_messenger_minter.add_remote_token_messenger(0, [11u8; 32]);
+        token_messenger_minter.link_token_pair(*stablecoin.address(), [10u8; 32], 0);
+    }
+    // Formats a message from the remote route of `configure_remote_route` minting 10 tokens.
+    fn format_remote_message(
+        token_messenger_minter: &TokenMessengerMinterHostRef,
+        mint_recipient: Address,
+        destination_caller: [u8; 32],
+        nonce: u64,
+    ) -> Vec<u8> {
+        let message_body: Vec<u8> = BurnMessage::format_message(
+            2,
+            &[10u8; 32],
+            &generic_address(mint_recipient),
+            10,
+            &[11u8; 32],
+        );
+        Message::format_message(
+            2,
+            0,
+            31,
+            nonce,
+            &[11u8; 32],
+            &generic_address(*token_messenger_minter.address()),
+            &destination_caller,
+            0,
+            &message_body,
+        )
+    }
+    // Returns the ethereum address of the secp256k1 attester with the given secret key.
+    fn attester_address(sk_bytes: [u8; 32]) -> EthAddress {
+        let (_, vk) = construct_keypair(sk_bytes);
+        recover_ethereum_address(vk.to_encoded_point(false).as_ref()[1..].try_into().unwrap())
+    }
+    // Signs the message with the given secp256k1 attester keys, in the given order.
+    fn attest(message: &[u8], signers: &[[u8; 32]]) -> Bytes {
+        let message_hash = Message::new(2, message).hash();
+        let mut attestation: Vec<u8> = vec![];
+        for sk_bytes in signers {
+            let (sk, _) = construct_keypair(*sk_bytes);
+            attestation.extend_from_slice(&sign_message(sk, &message_hash));
+        }
+        Bytes::from(attestation)
+    }
+    // Formats the message of `format_remote_message` and attests it with a single attester key.
+    fn attested_message(
+        token_messenger_minter: &TokenMessengerMinterHostRef,
+        mint_recipient: Address,
+        nonce: u64,
+        sk_bytes: [u8; 32],
+    ) -> (Bytes, Bytes) {
+        let message =
+            format_remote_message(token_messenger_minter, mint_recipient, [0u8; 32], nonce);
+        let attestation = attest(&message, &[sk_bytes]);
+        (Bytes::from(message), attestation)
+    }
     #[test]
     fn test_deposit_for_burn() {
         let (
@@ -173,11 +290,12 @@ mod test_setup {
         let message: Vec<u8> = Message::format_message(
             2,
             remote_domain,
-            32,
+            31,
             0,
             &remote_token_messenger,
             &generic_address(token_messenger_minter.address().clone()),
             &[0u8; 32],
+            0,
             &message_body,
         );
         let message_typed: Message = Message::new(2, &message);
@@ -191,12 +309,35 @@ mod test_setup {
         );
     }
 
+    #[test]
+    fn test_receive_message_to_blacklisted_recipient_fails() {
+        let CctpContracts {
+            env,
+            mut stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            blacklister,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let mint_recipient: Address = env.get_account(5);
+        env.set_caller(blacklister);
+        stablecoin.blacklist(&mint_recipient);
+        let message = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0);
+        let result =
+            message_transmitter.try_receive_message(Bytes::from(message), Bytes::from(vec![]));
+        assert_eq!(
+            result.err().unwrap(),
+            crate::stablecoin::errors::Error::AccountBlacklisted.into()
+        );
+        assert_eq!(stablecoin.balance_of(&mint_recipient), U256::zero());
+    }
     #[test]
     fn test_replace_message() {
+        use crate::message_transmitter::events::MessageSent;
         let (
             env,
             mut stablecoin,
-            message_transmitter,
+            mut message_transmitter,
             mut token_messenger_minter,
             owner,
             master_minter,
@@ -206,7 +347,6 @@ mod test_setup {
         let remote_token_address: [u8; 32] = [10u8; 32];
         let remote_token_messenger: [u8; 32] = [11u8; 32];
         let remote_domain: u32 = 0;
-        let mint_recipient: Address = env.get_account(0);
         env.set_caller(master_minter);
         stablecoin.configure_controller(&controller, token_messenger_minter.address());
         env.set_caller(controller);
@@ -219,36 +359,101 @@ mod test_setup {
             remote_token_address,
             remote_domain,
         );
-        let message_body: Vec<u8> = BurnMessage::format_message(
-            2,
-            &remote_token_address,
-            &generic_address(mint_recipient),
+        assert_eq!(message_transmitter.get_sent_message_hash(0), None);
+        stablecoin.approve(token_messenger_minter.address(), &10.into());
+        token_messenger_minter.deposit_for_burn(
             10,
-            &generic_address(owner),
+            remote_domain,
+            [1u8; 32],
+            *stablecoin.address(),
         );
-        let message: Vec<u8> = Message::format_message(
+        let message: Vec<u8> = env
+            .get_event::<MessageSent, _>(message_transmitter.address(), -1)
+            .unwrap()
+            .message;
+        let message_typed: Message = Message::new(2, &message);
+        assert_eq!(message_typed.nonce(), 0);
+        assert_eq!(
+            message_transmitter.get_sent_message_hash(0),
+            Some(message_typed.hash())
+        );
+        // the burn message is sent by the minter to the remote token messenger
+        let message_sender = generic_address_to_contract_address(message_typed.sender());
+        assert_eq!(&message_sender, token_messenger_minter.address());
+        assert_eq!(message_typed.recipient(), remote_token_messenger);
+
+        // a message that was never sent cannot be replaced
+        let unknown_message: Vec<u8> = Message::format_message(
             2,
             31,
-            0,
-            0,
-            &generic_address(token_messenger_minter.address().clone()),
-            &generic_address(token_messenger_minter.address().clone()),
+            remote_domain,
+            1,
+            &message_typed.sender(),
+            &message_typed.recipient(),
             &[0u8; 32],
-            &message_body,
+            0,
+            message_typed.message_body(),
         );
-        let message_typed: Message = Message::new(2, &message);
-        let message_recipient = message_typed.recipient();
-        let message_recipient_address = generic_address_to_contract_address(message_recipient);
-        assert_eq!(&message_recipient_address, token_messenger_minter.address());
+        let result = token_messenger_minter.try_replace_deposit_for_burn(
+            Bytes::from(unknown_message),
+            Bytes::from(vec![]),
+            [0u8; 32],
+            [2u8; 32],
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            crate::message_transmitter::errors::Error::UnknownOriginalMessage.into()
+        );
+
+        // a malformed message cannot be replaced
+        let result = message_transmitter.try_replace_message(
+            Bytes::from(vec![0u8; 10]),
+            Bytes::from(vec![]),
+            Bytes::from(message_typed.message_body().to_vec()),
+            [2u8; 32],
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            crate::message_transmitter::errors::Error::InvalidMessage.into()
+        );
+        // only the sender of the original message can replace it
+        let result = message_transmitter.try_replace_message(
+            Bytes::from(message.clone()),
+            Bytes::from(vec![]),
+            Bytes::from(message_typed.message_body().to_vec()),
+            [2u8; 32],
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            crate::message_transmitter::errors::Error::InvalidMessageSender.into()
+        );
+
         token_messenger_minter.replace_deposit_for_burn(
             Bytes::from(message.clone()),
             Bytes::from(vec![]),
             [0u8; 32],
-            [1u8; 32],
+            [2u8; 32],
         );
-        assert!(
-            env.emitted(message_transmitter.address(), "MessageSent"),
-            "MessageSent event not emitted"
+        let new_message: Vec<u8> = env
+            .get_event::<MessageSent, _>(message_transmitter.address(), -1)
+            .unwrap()
+            .message;
+        let new_message_typed: Message = Message::new(2, &new_message);
+        assert_eq!(new_message_typed.nonce(), 0);
+        assert_eq!(
+            message_transmitter.get_sent_message_hash(0),
+            Some(new_message_typed.hash())
+        );
+        // the replaced message can no longer be replaced
+        let result = token_messenger_minter.try_replace_deposit_for_burn(
+            Bytes::from(message),
+            Bytes::from(vec![]),
+            [0u8; 32],
+            [3u8; 32],
+        );
+        assert_eq!(
+            result.err().unwrap(),
+            crate::message_transmitter::errors::Error::UnknownOriginalMessage.into()
         );
     }
     #[test]
@@ -290,11 +495,12 @@ mod test_setup {
         let message: Vec<u8> = Message::format_message(
             2,
             remote_domain,
-            32,
+            31,
             0,
             &remote_token_messenger,
             &generic_address(token_messenger_minter.address().clone()),
             &[0u8; 32],
+            0,
             &message_body,
         );
         let message_typed: Message = Message::new(2, &message);
@@ -339,4 +545,1255 @@ mod test_setup {
             "MessageReceived event not emitted"
         );
     }
+    #[test]
+    fn test_migrate_cctp_contracts() {
+        let (env, _, mut message_transmitter, mut token_messenger_minter, owner, ..) =
+            setup_cctp_contracts();
+        assert_eq!(
+            message_transmitter.storage_version(),
+            crate::message_transmitter::STORAGE_VERSION
+        );
+        assert_eq!(
+            token_messenger_minter.storage_version(),
+            crate::token_messenger_minter::STORAGE_VERSION
+        );
+        env.set_caller(env.get_account(5));
+        assert_eq!(
+            message_transmitter
+                .try_migrate(vec![], vec![], vec![], vec![])
+                .err()
+                .unwrap(),
+            crate::security::errors::Error::NotOwner.into()
+        );
+        assert_eq!(
+            token_messenger_minter.try_migrate().err().unwrap(),
+            crate::security::errors::Error::NotOwner.into()
+        );
+        env.set_caller(owner);
+        assert_eq!(
+            message_transmitter
+                .try_migrate(vec![], vec![], vec![], vec![])
+                .err()
+                .unwrap(),
+            crate::message_transmitter::errors::Error::StorageUpToDate.into()
+        );
+        assert_eq!(
+            token_messenger_minter.try_migrate().err().unwrap(),
+            crate::token_messenger_minter::errors::Error::StorageUpToDate.into()
+        );
+    }
+    #[test]
+    fn test_two_step_ownership_transfer() {
+        use crate::security::errors::Error::{NoPendingOwner, NotOwner, NotPendingOwner};
+        let (env, _, mut message_transmitter, mut token_messenger_minter, owner, ..) =
+            setup_cctp_contracts();
+        let new_owner = env.get_account(5);
+        assert_eq!(message_transmitter.owner(), Some(owner));
+        assert_eq!(
+            message_transmitter.try_accept_ownership().err().unwrap(),
+            NoPendingOwner.into()
+        );
+        env.set_caller(new_owner);
+        assert_eq!(
+            message_transmitter
+                .try_transfer_ownership(new_owner)
+                .err()
+                .unwrap(),
+            NotOwner.into()
+        );
+        env.set_caller(owner);
+        message_transmitter.transfer_ownership(new_owner);
+        assert!(
+            env.emitted(message_transmitter.address(), "OwnershipTransferStarted"),
+            "OwnershipTransferStarted event not emitted"
+        );
+        assert_eq!(message_transmitter.pending_owner(), Some(new_owner));
+        assert_eq!(
+            message_transmitter.try_accept_ownership().err().unwrap(),
+            NotPendingOwner.into()
+        );
+        env.set_caller(new_owner);
+        message_transmitter.accept_ownership();
+        assert!(
+            env.emitted(message_transmitter.address(), "OwnershipTransferred"),
+            "OwnershipTransferred event not emitted"
+        );
+        assert_eq!(message_transmitter.owner(), Some(new_owner));
+        assert_eq!(message_transmitter.pending_owner(), None);
+
+        env.set_caller(owner);
+        token_messenger_minter.transfer_ownership(new_owner);
+        token_messenger_minter.cancel_ownership_transfer();
+        assert!(
+            env.emitted(
+                token_messenger_minter.address(),
+                "OwnershipTransferCanceled"
+            ),
+            "OwnershipTransferCanceled event not emitted"
+        );
+        env.set_caller(new_owner);
+        assert_eq!(
+            token_messenger_minter.try_accept_ownership().err().unwrap(),
+            NoPendingOwner.into()
+        );
+        env.set_caller(owner);
+        token_messenger_minter.renounce_ownership();
+        assert_eq!(token_messenger_minter.owner(), None);
+        assert_eq!(
+            token_messenger_minter
+                .try_set_pauser(owner, true)
+                .err()
+                .unwrap(),
+            NotOwner.into()
+        );
+    }
+    #[test]
+    fn test_pause_and_unpause() {
+        use crate::security::errors::Error::{
+            ContractIsNotPaused, ContractIsPaused, NotOwner, NotPauser,
+        };
+        let (env, _, mut message_transmitter, mut token_messenger_minter, owner, ..) =
+            setup_cctp_contracts();
+        let pauser = env.get_account(5);
+        assert_eq!(
+            message_transmitter.try_pause().err().unwrap(),
+            NotPauser.into()
+        );
+        env.set_caller(pauser);
+        assert_eq!(
+            message_transmitter
+                .try_set_pauser(pauser, true)
+                .err()
+                .unwrap(),
+            NotOwner.into()
+        );
+        env.set_caller(owner);
+        message_transmitter.set_pauser(pauser, true);
+        token_messenger_minter.set_pauser(pauser, true);
+        assert!(
+            env.emitted(message_transmitter.address(), "PauserChanged"),
+            "PauserChanged event not emitted"
+        );
+        assert!(message_transmitter.is_pauser(pauser));
+        env.set_caller(pauser);
+        assert_eq!(
+            message_transmitter.try_unpause().err().unwrap(),
+            ContractIsNotPaused.into()
+        );
+        message_transmitter.pause();
+        assert!(message_transmitter.is_paused());
+        assert!(
+            env.emitted(message_transmitter.address(), "Paused"),
+            "Paused event not emitted"
+        );
+        assert_eq!(
+            message_transmitter
+                .try_send_message(0, [1u8; 32], Bytes::from(vec![1u8]))
+                .err()
+                .unwrap(),
+            ContractIsPaused.into()
+        );
+        message_transmitter.unpause();
+        assert!(
+            env.emitted(message_transmitter.address(), "Unpaused"),
+            "Unpaused event not emitted"
+        );
+        message_transmitter.send_message(0, [1u8; 32], Bytes::from(vec![1u8]));
+
+        token_messenger_minter.pause();
+        assert!(token_messenger_minter.is_paused());
+        assert_eq!(
+            token_messenger_minter.try_pause().err().unwrap(),
+            ContractIsPaused.into()
+        );
+    }
+    #[test]
+    fn test_per_domain_pause() {
+        use crate::security::errors::Error::{
+            DestinationDomainPaused, NotPauser, SourceDomainPaused,
+        };
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let pauser = env.get_account(5);
+        let remote_domain: u32 = 0;
+        let mint_recipient: Address = env.get_account(0);
+        message_transmitter.add_remote_domain(5);
+        message_transmitter.add_remote_domain(6);
+        message_transmitter.set_pauser(pauser, true);
+        assert_eq!(
+            message_transmitter
+                .try_set_source_domain_paused(remote_domain, true)
+                .err()
+                .unwrap(),
+            NotPauser.into()
+        );
+        env.set_caller(pauser);
+        message_transmitter.set_source_domain_paused(remote_domain, true);
+        message_transmitter.set_destination_domain_paused(5, true);
+        assert!(
+            env.emitted(message_transmitter.address(), "SourceDomainPauseChanged"),
+            "SourceDomainPauseChanged event not emitted"
+        );
+        assert!(message_transmitter.is_source_domain_paused(remote_domain));
+        assert!(!message_transmitter.is_destination_domain_paused(remote_domain));
+
+        // outbound routes to other domains keep working
+        assert_eq!(
+            message_transmitter
+                .try_send_message(5, [1u8; 32], Bytes::from(vec![1u8]))
+                .err()
+                .unwrap(),
+            DestinationDomainPaused.into()
+        );
+        message_transmitter.send_message(6, [1u8; 32], Bytes::from(vec![1u8]));
+
+        let message = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0);
+        let result = message_transmitter
+            .try_receive_message(Bytes::from(message.clone()), Bytes::from(vec![]));
+        assert_eq!(result.err().unwrap(), SourceDomainPaused.into());
+        message_transmitter.set_source_domain_paused(remote_domain, false);
+        message_transmitter.receive_message(Bytes::from(message), Bytes::from(vec![]));
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 1_000_000_010.into());
+    }
+    #[test]
+    fn test_token_messenger_minter_per_domain_pause() {
+        use crate::security::errors::Error::{DestinationDomainPaused, SourceDomainPaused};
+        let CctpContracts {
+            env,
+            mut stablecoin,
+            mut message_transmitter,
+            mut token_messenger_minter,
+            owner,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let pauser = env.get_account(4);
+        let user = env.get_account(5);
+        env.set_caller(owner);
+        token_messenger_minter.set_pauser(pauser, true);
+
+        // handle_receive_message rejects mints from a paused source domain
+        env.set_caller(pauser);
+        token_messenger_minter.set_source_domain_paused(0, true);
+        assert!(token_messenger_minter.is_source_domain_paused(0));
+        assert!(!message_transmitter.is_source_domain_paused(0));
+        let message = format_remote_message(&token_messenger_minter, user, [0u8; 32], 0);
+        let result = message_transmitter
+            .try_receive_message(Bytes::from(message.clone()), Bytes::from(vec![]));
+        assert_eq!(result.err().unwrap(), SourceDomainPaused.into());
+        token_messenger_minter.set_source_domain_paused(0, false);
+        message_transmitter.receive_message(Bytes::from(message), Bytes::from(vec![]));
+        assert_eq!(stablecoin.balance_of(&user), 10.into());
+
+        // deposit_for_burn rejects burns to a paused destination domain
+        token_messenger_minter.set_destination_domain_paused(0, true);
+        env.set_caller(user);
+        stablecoin.approve(token_messenger_minter.address(), &10.into());
+        assert_eq!(
+            token_messenger_minter
+                .try_deposit_for_burn(10, 0, [1u8; 32], *stablecoin.address())
+                .err()
+                .unwrap(),
+            DestinationDomainPaused.into()
+        );
+        env.set_caller(pauser);
+        token_messenger_minter.set_destination_domain_paused(0, false);
+        env.set_caller(user);
+        token_messenger_minter.deposit_for_burn(10, 0, [1u8; 32], *stablecoin.address());
+        assert_eq!(stablecoin.balance_of(&user), 0.into());
+    }
+    #[test]
+    fn test_remote_domain_allowlist() {
+        use crate::message_transmitter::errors::Error::{
+            InvalidDestinationDomain, RemoteDomainAlreadyAdded, UnknownRemoteDomain,
+        };
+        let (env, _, mut message_transmitter, _, owner, ..) = setup_cctp_contracts();
+        assert!(message_transmitter.is_remote_domain(0));
+        env.set_caller(env.get_account(5));
+        assert_eq!(
+            message_transmitter.try_add_remote_domain(3).err().unwrap(),
+            crate::security::errors::Error::NotOwner.into()
+        );
+        env.set_caller(owner);
+        assert_eq!(
+            message_transmitter.try_add_remote_domain(31).err().unwrap(),
+            InvalidDestinationDomain.into()
+        );
+        assert_eq!(
+            message_transmitter.try_add_remote_domain(0).err().unwrap(),
+            RemoteDomainAlreadyAdded.into()
+        );
+        assert_eq!(
+            message_transmitter
+                .try_send_message(31, [1u8; 32], Bytes::from(vec![1u8]))
+                .err()
+                .unwrap(),
+            InvalidDestinationDomain.into()
+        );
+        assert_eq!(
+            message_transmitter
+                .try_send_message(3, [1u8; 32], Bytes::from(vec![1u8]))
+                .err()
+                .unwrap(),
+            UnknownRemoteDomain.into()
+        );
+        message_transmitter.add_remote_domain(3);
+        assert!(
+            env.emitted(message_transmitter.address(), "RemoteDomainAdded"),
+            "RemoteDomainAdded event not emitted"
+        );
+        assert_eq!(
+            message_transmitter.send_message(3, [1u8; 32], Bytes::from(vec![1u8])),
+            0
+        );
+        message_transmitter.remove_remote_domain(3);
+        assert!(
+            env.emitted(message_transmitter.address(), "RemoteDomainRemoved"),
+            "RemoteDomainRemoved event not emitted"
+        );
+        assert_eq!(
+            message_transmitter
+                .try_remove_remote_domain(3)
+                .err()
+                .unwrap(),
+            UnknownRemoteDomain.into()
+        );
+        assert_eq!(
+            message_transmitter
+                .try_send_message(3, [1u8; 32], Bytes::from(vec![1u8]))
+                .err()
+                .unwrap(),
+            UnknownRemoteDomain.into()
+        );
+    }
+    #[test]
+    fn test_receive_message_with_account_destination_caller() {
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let relayer = env.get_account(5);
+        let mint_recipient = env.get_account(6);
+        let message = format_remote_message(
+            &token_messenger_minter,
+            mint_recipient,
+            generic_address(relayer),
+            0,
+        );
+        let result = message_transmitter
+            .try_receive_message(Bytes::from(message.clone()), Bytes::from(vec![]));
+        assert_eq!(
+            result.err().unwrap(),
+            crate::message_transmitter::errors::Error::InvalidDestinationCaller.into()
+        );
+        env.set_caller(relayer);
+        message_transmitter.receive_message(Bytes::from(message), Bytes::from(vec![]));
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 10.into());
+    }
+    #[test]
+    fn test_receive_message_with_contract_destination_caller() {
+        use crate::message_transmitter::relayer_mock::RelayerMockHostRef;
+        use odra::host::NoArgs;
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let mut relayer = RelayerMockHostRef::deploy(&env, NoArgs);
+        let mint_recipient = env.get_account(6);
+        let message = format_remote_message(
+            &token_messenger_minter,
+            mint_recipient,
+            generic_address(*relayer.address()),
+            0,
+        );
+        // neither the transmitter itself nor any other account may deliver the message
+        let result = message_transmitter
+            .try_receive_message(Bytes::from(message.clone()), Bytes::from(vec![]));
+        assert_eq!(
+            result.err().unwrap(),
+            crate::message_transmitter::errors::Error::InvalidDestinationCaller.into()
+        );
+        relayer.relay(
+            *message_transmitter.address(),
+            Bytes::from(message),
+            Bytes::from(vec![]),
+        );
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 10.into());
+    }
+    #[test]
+    fn test_receive_message_validation() {
+        use crate::message_transmitter::errors::Error::{
+            InvalidDestinationDomain, InvalidMessageRecipient, MessageBodyTooLarge,
+        };
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let mint_recipient = env.get_account(6);
+        let message = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0);
+        let message_typed: Message = Message::new(2, &message);
+
+        // attested for another chain
+        let message_for_other_domain: Vec<u8> = Message::format_message(
+            2,
+            0,
+            32,
+            0,
+            &message_typed.sender(),
+            &message_typed.recipient(),
+            &[0u8; 32],
+            0,
+            message_typed.message_body(),
+        );
+        let result = message_transmitter
+            .try_receive_message(Bytes::from(message_for_other_domain), Bytes::from(vec![]));
+        assert_eq!(result.err().unwrap(), InvalidDestinationDomain.into());
+
+        // addressed to the transmitter itself
+        let message_to_transmitter: Vec<u8> = Message::format_message(
+            2,
+            0,
+            31,
+            0,
+            &message_typed.sender(),
+            &generic_address(*message_transmitter.address()),
+            &[0u8; 32],
+            0,
+            message_typed.message_body(),
+        );
+        let result = message_transmitter
+            .try_receive_message(Bytes::from(message_to_transmitter), Bytes::from(vec![]));
+        assert_eq!(result.err().unwrap(), InvalidMessageRecipient.into());
+
+        // addressed to an account, which cannot handle the message
+        let message_to_account: Vec<u8> = Message::format_message(
+            2,
+            0,
+            31,
+            0,
+            &message_typed.sender(),
+            &generic_address(mint_recipient),
+            &[0u8; 32],
+            0,
+            message_typed.message_body(),
+        );
+        let result = message_transmitter
+            .try_receive_message(Bytes::from(message_to_account), Bytes::from(vec![]));
+        assert_eq!(result.err().unwrap(), InvalidMessageRecipient.into());
+        assert_eq!(
+            message_transmitter
+                .try_set_message_recipient(mint_recipient, true)
+                .err()
+                .unwrap(),
+            InvalidMessageRecipient.into()
+        );
+        assert_eq!(
+            message_transmitter
+                .try_set_message_recipient(*message_transmitter.address(), true)
+                .err()
+                .unwrap(),
+            InvalidMessageRecipient.into()
+        );
+
+        // body larger than the configured maximum
+        message_transmitter
+            .set_max_message_body_size(U256::from(message_typed.message_body().len() - 1));
+        let result = message_transmitter
+            .try_receive_message(Bytes::from(message.clone()), Bytes::from(vec![]));
+        assert_eq!(result.err().unwrap(), MessageBodyTooLarge.into());
+
+        message_transmitter
+            .set_max_message_body_size(U256::from(message_typed.message_body().len()));
+        message_transmitter.receive_message(Bytes::from(message), Bytes::from(vec![]));
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 10.into());
+    }
+    #[test]
+    fn test_receive_messages() {
+        use crate::message_transmitter::errors::Error::{EmptyBatch, NonceAlreadyUsed};
+        use crate::message_transmitter::events::MessageReceiveFailed;
+        use crate::message_transmitter::utils::ReceiveMode;
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let mint_recipient = env.get_account(6);
+        let first = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0);
+        let second = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 1);
+        let batch: Vec<(Bytes, Bytes)> = vec![
+            (Bytes::from(first.clone()), Bytes::from(vec![])),
+            (Bytes::from(second), Bytes::from(vec![])),
+            // replays the first message
+            (Bytes::from(first.clone()), Bytes::from(vec![])),
+        ];
+        let result = message_transmitter.try_receive_messages(vec![], ReceiveMode::SkipFailed);
+        assert_eq!(result.err().unwrap(), EmptyBatch.into());
+
+        let result =
+            message_transmitter.try_receive_messages(batch.clone(), ReceiveMode::AllOrNothing);
+        assert_eq!(result.err().unwrap(), NonceAlreadyUsed.into());
+        assert_eq!(stablecoin.balance_of(&mint_recipient), U256::zero());
+
+        let received = message_transmitter.receive_messages(batch, ReceiveMode::SkipFailed);
+        assert_eq!(received, 2);
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 20.into());
+        assert_eq!(
+            env.get_event(message_transmitter.address(), -1),
+            Ok(MessageReceiveFailed {
+                index: 2,
+                message_hash: Message::new(2, &first).hash(),
+                error: NonceAlreadyUsed as u32,
+            })
+        );
+    }
+    #[test]
+    fn test_message_expiration() {
+        use crate::message_transmitter::errors::Error::{
+            ExpiringMessagesNotSupported, InvalidExpiration, MessageExpired,
+        };
+        use crate::message_transmitter::events::MessageSent;
+        use crate::message_transmitter::message::EXPIRING_VERSION_FLAG;
+        use crate::security::errors::Error::NotOwner;
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let now: u64 = env.block_time();
+        // expiring messages are only sent to domains known to parse their layout
+        assert!(!message_transmitter.supports_expiring_messages(0));
+        let result = message_transmitter.try_send_message_with_expiration(
+            0,
+            [11u8; 32],
+            Bytes::from(vec![1u8]),
+            [0u8; 32],
+            now + 1_000,
+        );
+        assert_eq!(result.err().unwrap(), ExpiringMessagesNotSupported.into());
+        env.set_caller(env.get_account(5));
+        let result = message_transmitter.try_set_expiring_messages_supported(0, true);
+        assert_eq!(result.err().unwrap(), NotOwner.into());
+        env.set_caller(env.get_account(0));
+        message_transmitter.set_expiring_messages_supported(0, true);
+        assert!(message_transmitter.supports_expiring_messages(0));
+        // an expiration that is not in the future is rejected when sending
+        let result = message_transmitter.try_send_message_with_expiration(
+            0,
+            [11u8; 32],
+            Bytes::from(vec![1u8]),
+            [0u8; 32],
+            now,
+        );
+        assert_eq!(result.err().unwrap(), InvalidExpiration.into());
+        message_transmitter.send_message_with_expiration(
+            0,
+            [11u8; 32],
+            Bytes::from(vec![1u8]),
+            [0u8; 32],
+            now + 1_000,
+        );
+        let sent: MessageSent = env.get_event(message_transmitter.address(), -1).unwrap();
+        assert_eq!(Message::new(2, &sent.message).expiration(), now + 1_000);
+        // expiring messages flag their version and move the body behind the expiration
+        assert_eq!(
+            sent.message[..4],
+            (2u32 | EXPIRING_VERSION_FLAG).to_be_bytes()
+        );
+        assert_eq!(sent.message[124..], [1u8]);
+
+        let mint_recipient = env.get_account(6);
+        let remote_message =
+            format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0);
+        let remote_message_typed: Message = Message::new(2, &remote_message);
+        let expiring_message = |nonce: u64, expiration: u64| -> Vec<u8> {
+            Message::format_message(
+                2,
+                0,
+                31,
+                nonce,
+                &remote_message_typed.sender(),
+                &remote_message_typed.recipient(),
+                &[0u8; 32],
+                expiration,
+                remote_message_typed.message_body(),
+            )
+        };
+        let first = expiring_message(0, now + 1_000);
+        let second = expiring_message(1, now + 1_000);
+        message_transmitter.receive_message(Bytes::from(first), Bytes::from(vec![]));
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 10.into());
+
+        env.advance_block_time(1_000);
+        let result =
+            message_transmitter.try_receive_message(Bytes::from(second), Bytes::from(vec![]));
+        assert_eq!(result.err().unwrap(), MessageExpired.into());
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 10.into());
+    }
+    #[test]
+    fn test_receive_message_in_original_layout() {
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let mint_recipient = env.get_account(6);
+        let message_body: Vec<u8> = BurnMessage::format_message(
+            2,
+            &[10u8; 32],
+            &generic_address(mint_recipient),
+            10,
+            &[11u8; 32],
+        );
+        // version, source domain, destination domain, nonce, sender, recipient and
+        // destination caller, followed by the body at offset 116
+        let mut message: Vec<u8> = Vec::new();
+        message.extend_from_slice(&2u32.to_be_bytes());
+        message.extend_from_slice(&0u32.to_be_bytes());
+        message.extend_from_slice(&31u32.to_be_bytes());
+        message.extend_from_slice(&0u64.to_be_bytes());
+        message.extend_from_slice(&[11u8; 32]);
+        message.extend_from_slice(&generic_address(*token_messenger_minter.address()));
+        message.extend_from_slice(&[0u8; 32]);
+        assert_eq!(message.len(), 116);
+        message.extend_from_slice(&message_body);
+        assert_eq!(
+            message,
+            format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0)
+        );
+        let message_typed: Message = Message::new(2, &message);
+        assert!(!message_typed.is_expiring());
+        assert_eq!(message_typed.expiration(), 0);
+        assert_eq!(message_typed.message_body(), message_body.as_slice());
+
+        // messages in the original layout never expire
+        env.advance_block_time(1_000_000);
+        message_transmitter.receive_message(Bytes::from(message), Bytes::from(vec![]));
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 10.into());
+    }
+    #[test]
+    fn test_receive_message_with_ed25519_attester() {
+        use crate::message_transmitter::attestation::{AttesterKeyKind, ATTESTATION_VERSION};
+        use crate::message_transmitter::errors::Error::{
+            InvalidAttestationLength, InvalidAttesterSignature, UnknownAttesterKeyKind,
+        };
+        use odra::casper_types::{bytesrepr::ToBytes, crypto, PublicKey, SecretKey};
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            owner,
+            ..
+        } = setup_cctp_contracts_with_remote_route(2);
+        let mint_recipient = env.get_account(6);
+        let message = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0);
+        let message_hash = Message::new(2, &message).hash();
+
+        let (secp256k1_sk, secp256k1_vk) = construct_keypair([1u8; 32]);
+        let secp256k1_attester = recover_ethereum_address(
+            secp256k1_vk.to_encoded_point(false).as_ref()[1..]
+                .try_into()
+                .unwrap(),
+        );
+        let ed25519_sk = SecretKey::ed25519_from_bytes([3u8; 32]).unwrap();
+        let ed25519_pk = PublicKey::from(&ed25519_sk);
+        // casper keys and signatures are serialized with a leading tag byte
+        let ed25519_attester: [u8; 32] = ed25519_pk.to_bytes().unwrap()[1..].try_into().unwrap();
+        let ed25519_signature: Vec<u8> = crypto::sign(message_hash, &ed25519_sk, &ed25519_pk)
+            .to_bytes()
+            .unwrap()[1..]
+            .to_vec();
+        env.set_caller(owner);
+        message_transmitter.enable_attester(secp256k1_attester);
+        message_transmitter.enable_ed25519_attester(ed25519_attester);
+        assert!(message_transmitter.is_ed25519_attester(ed25519_attester));
+
+        let secp256k1_entry: Vec<u8> = [
+            vec![AttesterKeyKind::Secp256k1Eth as u8],
+            sign_message(secp256k1_sk, &message_hash).to_vec(),
+        ]
+        .concat();
+        let ed25519_entry: Vec<u8> = [
+            vec![AttesterKeyKind::Ed25519 as u8],
+            ed25519_attester.to_vec(),
+            ed25519_signature.clone(),
+        ]
+        .concat();
+        let attestation = |entries: &[&Vec<u8>]| -> Bytes {
+            let mut attestation = vec![ATTESTATION_VERSION];
+            for entry in entries {
+                attestation.extend_from_slice(entry);
+            }
+            Bytes::from(attestation)
+        };
+
+        // secp256k1 signatures have to precede ed25519 signatures
+        let result = message_transmitter.try_receive_message(
+            Bytes::from(message.clone()),
+            attestation(&[&ed25519_entry, &secp256k1_entry]),
+        );
+        assert_eq!(result.err().unwrap(), InvalidAttesterSignature.into());
+        // the same attester cannot sign twice
+        let result = message_transmitter.try_receive_message(
+            Bytes::from(message.clone()),
+            attestation(&[&ed25519_entry, &ed25519_entry]),
+        );
+        assert_eq!(result.err().unwrap(), InvalidAttesterSignature.into());
+        // a signature of another message
+        let mut forged_entry = ed25519_entry.clone();
+        forged_entry[40] ^= 1;
+        let result = message_transmitter.try_receive_message(
+            Bytes::from(message.clone()),
+            attestation(&[&secp256k1_entry, &forged_entry]),
+        );
+        assert_eq!(result.err().unwrap(), InvalidAttesterSignature.into());
+        let result = message_transmitter.try_receive_message(
+            Bytes::from(message.clone()),
+            attestation(&[&secp256k1_entry, &vec![2u8; 97]]),
+        );
+        assert_eq!(result.err().unwrap(), UnknownAttesterKeyKind.into());
+        let result = message_transmitter.try_receive_message(
+            Bytes::from(message.clone()),
+            attestation(&[&secp256k1_entry]),
+        );
+        assert_eq!(result.err().unwrap(), InvalidAttestationLength.into());
+
+        message_transmitter.receive_message(
+            Bytes::from(message),
+            attestation(&[&secp256k1_entry, &ed25519_entry]),
+        );
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 10.into());
+    }
+    #[test]
+    fn test_weighted_attester_quorum() {
+        use crate::message_transmitter::errors::Error::InsufficientAttesterWeight;
+        use crate::security::errors::Error::NotOwner;
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            owner,
+            ..
+        } = setup_cctp_contracts_with_remote_route(2);
+        let mint_recipient = env.get_account(6);
+        let first = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0);
+        let second = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 1);
+
+        // attesters sorted by their ethereum address
+        let mut attesters: Vec<(EthAddress, [u8; 32])> = [[1u8; 32], [2u8; 32], [3u8; 32]]
+            .iter()
+            .map(|sk_bytes| (attester_address(*sk_bytes), *sk_bytes))
+            .collect();
+        attesters.sort();
+        let signers = |indices: &[usize]| -> Vec<[u8; 32]> {
+            indices.iter().map(|index| attesters[*index].1).collect()
+        };
+        env.set_caller(owner);
+        for (address, _) in &attesters {
+            message_transmitter.enable_attester(*address);
+        }
+        assert_eq!(message_transmitter.weight_threshold(), 0);
+        assert_eq!(message_transmitter.attester_weight(attesters[0].0), 1);
+        message_transmitter.set_attester_weight(attesters[0].0, 3);
+        message_transmitter.set_weight_threshold(4);
+        assert_eq!(message_transmitter.weight_threshold(), 4);
+        assert_eq!(message_transmitter.attester_weight(attesters[0].0), 3);
+        env.set_caller(env.get_account(5));
+        assert_eq!(
+            message_transmitter
+                .try_set_weight_threshold(1)
+                .err()
+                .unwrap(),
+            NotOwner.into()
+        );
+        assert_eq!(
+            message_transmitter
+                .try_set_attester_weight(attesters[1].0, 3)
+                .err()
+                .unwrap(),
+            NotOwner.into()
+        );
+
+        // two attesters of weight 1 do not reach the weight threshold
+        let result = message_transmitter.try_receive_message(
+            Bytes::from(first.clone()),
+            attest(&first, &signers(&[1, 2])),
+        );
+        assert_eq!(result.err().unwrap(), InsufficientAttesterWeight.into());
+        message_transmitter.receive_message(
+            Bytes::from(first.clone()),
+            attest(&first, &signers(&[0, 2])),
+        );
+        message_transmitter.receive_message(
+            Bytes::from(second.clone()),
+            attest(&second, &signers(&[0, 1])),
+        );
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 20.into());
+    }
+    #[test]
+    fn test_attester_rotation() {
+        use crate::message_transmitter::errors::Error::{
+            AttesterRotationInProgress, InvalidActivationTime, InvalidAttesterSignature,
+            NoAttesterRotation,
+        };
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            owner,
+            ..
+        } = setup_cctp_contracts_with_remote_route(1);
+        let mint_recipient = env.get_account(6);
+        let old_attester = attester_address([1u8; 32]);
+        let new_attester = attester_address([2u8; 32]);
+        env.set_caller(owner);
+        message_transmitter.enable_attester(old_attester);
+        env.advance_block_time(1_000);
+        let now: u64 = env.block_time();
+        assert_eq!(
+            message_transmitter
+                .try_schedule_attester_rotation(vec![], vec![], vec![], vec![], now - 1, 0)
+                .err()
+                .unwrap(),
+            InvalidActivationTime.into()
+        );
+        assert_eq!(
+            message_transmitter
+                .try_cancel_attester_rotation()
+                .err()
+                .unwrap(),
+            NoAttesterRotation.into()
+        );
+        message_transmitter.schedule_attester_rotation(
+            vec![new_attester],
+            vec![old_attester],
+            vec![],
+            vec![],
+            now + 1_000,
+            500,
+        );
+        assert_eq!(
+            message_transmitter
+                .get_attester_rotation()
+                .unwrap()
+                .activation_time,
+            now + 1_000
+        );
+        assert_eq!(message_transmitter.get_attesters(), vec![old_attester]);
+
+        // the new attester is not accepted before the activation time
+        let (message, attestation) =
+            attested_message(&token_messenger_minter, mint_recipient, 0, [2u8; 32]);
+        let result = message_transmitter.try_receive_message(message, attestation);
+        assert_eq!(result.err().unwrap(), InvalidAttesterSignature.into());
+        let (message, attestation) =
+            attested_message(&token_messenger_minter, mint_recipient, 0, [1u8; 32]);
+        message_transmitter.receive_message(message, attestation);
+
+        // both sets are accepted during the grace period
+        env.advance_block_time(1_000);
+        assert_eq!(
+            message_transmitter.get_attesters(),
+            vec![old_attester, new_attester]
+        );
+        let (message, attestation) =
+            attested_message(&token_messenger_minter, mint_recipient, 1, [1u8; 32]);
+        message_transmitter.receive_message(message, attestation);
+        let (message, attestation) =
+            attested_message(&token_messenger_minter, mint_recipient, 2, [2u8; 32]);
+        message_transmitter.receive_message(message, attestation);
+        assert_eq!(
+            message_transmitter
+                .try_cancel_attester_rotation()
+                .err()
+                .unwrap(),
+            AttesterRotationInProgress.into()
+        );
+        assert_eq!(
+            message_transmitter
+                .try_finalize_attester_rotation()
+                .err()
+                .unwrap(),
+            AttesterRotationInProgress.into()
+        );
+
+        // the old attester is rejected once the grace period is over
+        env.advance_block_time(500);
+        assert_eq!(message_transmitter.get_attesters(), vec![new_attester]);
+        let (message, attestation) =
+            attested_message(&token_messenger_minter, mint_recipient, 3, [1u8; 32]);
+        let result = message_transmitter.try_receive_message(message, attestation);
+        assert_eq!(result.err().unwrap(), InvalidAttesterSignature.into());
+        env.set_caller(env.get_account(5));
+        message_transmitter.finalize_attester_rotation();
+        assert_eq!(message_transmitter.get_attester_rotation(), None);
+        assert!(!message_transmitter.is_attester(old_attester));
+        assert!(message_transmitter.is_attester(new_attester));
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 30.into());
+    }
+    #[test]
+    fn test_disable_attester_during_rotation() {
+        use crate::message_transmitter::errors::Error::{
+            InvalidAttesterSignature, OverlappingAttesterRotation,
+        };
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            ..
+        } = setup_cctp_contracts_with_remote_route(1);
+        let mint_recipient = env.get_account(6);
+        let old_attester = attester_address([1u8; 32]);
+        let new_attester = attester_address([2u8; 32]);
+        message_transmitter.enable_attester(old_attester);
+        let now: u64 = env.block_time();
+
+        // an attester cannot be enabled and disabled by the same rotation
+        assert_eq!(
+            message_transmitter
+                .try_schedule_attester_rotation(
+                    vec![new_attester],
+                    vec![new_attester, old_attester],
+                    vec![],
+                    vec![],
+                    now + 1_000,
+                    500,
+                )
+                .err()
+                .unwrap(),
+            OverlappingAttesterRotation.into()
+        );
+        assert_eq!(
+            message_transmitter
+                .try_schedule_attester_rotation(
+                    vec![],
+                    vec![],
+                    vec![[7u8; 32]],
+                    vec![[7u8; 32]],
+                    now + 1_000,
+                    500,
+                )
+                .err()
+                .unwrap(),
+            OverlappingAttesterRotation.into()
+        );
+
+        // disabling the attester the rotation enables drops it from the rotation
+        message_transmitter.schedule_attester_rotation(
+            vec![new_attester],
+            vec![old_attester],
+            vec![],
+            vec![],
+            now + 1_000,
+            500,
+        );
+        message_transmitter.disable_attester(new_attester);
+        let rotation = message_transmitter.get_attester_rotation().unwrap();
+        assert!(rotation.enabled_attesters.is_empty());
+        assert_eq!(rotation.disabled_attesters, vec![old_attester]);
+        env.advance_block_time(1_000);
+        assert!(!message_transmitter.is_attester(new_attester));
+        let (message, attestation) =
+            attested_message(&token_messenger_minter, mint_recipient, 0, [2u8; 32]);
+        let result = message_transmitter.try_receive_message(message, attestation);
+        assert_eq!(result.err().unwrap(), InvalidAttesterSignature.into());
+
+        // disabling the attester in its grace period rejects it right away
+        assert!(message_transmitter.is_attester(old_attester));
+        message_transmitter.disable_attester(old_attester);
+        assert!(!message_transmitter.is_attester(old_attester));
+        let (message, attestation) =
+            attested_message(&token_messenger_minter, mint_recipient, 0, [1u8; 32]);
+        let result = message_transmitter.try_receive_message(message, attestation);
+        assert_eq!(result.err().unwrap(), InvalidAttesterSignature.into());
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 0.into());
+    }
+    #[test]
+    fn test_receive_message_with_malformed_signature() {
+        use crate::message_transmitter::errors::Error::{
+            InvalidSignature, InvalidSignatureRecoveryId, InvalidSignatureS,
+        };
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            token_messenger_minter,
+            owner,
+            ..
+        } = setup_cctp_contracts_with_remote_route(1);
+        let mint_recipient = env.get_account(6);
+        let message = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0);
+        let message_hash = Message::new(2, &message).hash();
+        let (attester_sk, _) = construct_keypair([1u8; 32]);
+        env.set_caller(owner);
+        message_transmitter.enable_attester(attester_address([1u8; 32]));
+        let (signature, recovery_id): (Signature, RecoveryId) =
+            attester_sk.sign_prehash_recoverable(&message_hash).unwrap();
+        let attestation = |signature: &Signature, recovery_byte: u8| -> Bytes {
+            let mut signature_bytes = signature.to_bytes().to_vec();
+            signature_bytes.push(recovery_byte);
+            Bytes::from(signature_bytes)
+        };
+
+        let high_s = Signature::from_scalars(signature.r(), -signature.s()).unwrap();
+        let result = message_transmitter.try_receive_message(
+            Bytes::from(message.clone()),
+            attestation(&high_s, (recovery_id.to_byte() ^ 1) + 27),
+        );
+        assert_eq!(result.err().unwrap(), InvalidSignatureS.into());
+        let result = message_transmitter.try_receive_message(
+            Bytes::from(message.clone()),
+            attestation(&signature, recovery_id.to_byte() + 29),
+        );
+        assert_eq!(result.err().unwrap(), InvalidSignatureRecoveryId.into());
+        let mut zero_signature = vec![0u8; 64];
+        zero_signature.push(27);
+        let result = message_transmitter
+            .try_receive_message(Bytes::from(message.clone()), Bytes::from(zero_signature));
+        assert_eq!(result.err().unwrap(), InvalidSignature.into());
+
+        message_transmitter.receive_message(
+            Bytes::from(message),
+            attestation(&signature, recovery_id.to_byte() + 27),
+        );
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 10.into());
+    }
+    #[test]
+    fn test_timelocked_owner_actions() {
+        use crate::security::errors::Error::{NotGuardian, OperationNotReady, UnknownOperation};
+        use crate::security::timelock::{operation_id, GRACE_PERIOD};
+        let (env, _, mut message_transmitter, mut token_messenger_minter, owner, ..) =
+            setup_cctp_contracts();
+        let guardian = env.get_account(5);
+        let attester: [u8; 20] = [7u8; 20];
+        env.set_caller(owner);
+        // without a delay, owner actions take effect immediately
+        message_transmitter.set_guardian(Some(guardian));
+        message_transmitter.set_timelock_delay(1_000);
+        assert_eq!(message_transmitter.get_guardian(), Some(guardian));
+        assert_eq!(message_transmitter.timelock_delay(), 1_000);
+
+        let enable_attester = operation_id("enable_attester", &attester).unwrap();
+        let now: u64 = env.block_time();
+        message_transmitter.enable_attester(attester);
+        assert!(!message_transmitter.is_attester(attester));
+        assert_eq!(
+            message_transmitter.get_operation_eta(enable_attester),
+            Some(now + 1_000)
+        );
+        assert_eq!(
+            message_transmitter
+                .try_enable_attester(attester)
+                .err()
+                .unwrap(),
+            OperationNotReady.into()
+        );
+
+        // the guardian cancels the queued action
+        assert_eq!(
+            message_transmitter
+                .try_cancel_operation(enable_attester)
+                .err()
+                .unwrap(),
+            NotGuardian.into()
+        );
+        env.set_caller(guardian);
+        message_transmitter.cancel_operation(enable_attester);
+        assert_eq!(message_transmitter.get_operation_eta(enable_attester), None);
+        assert_eq!(
+            message_transmitter
+                .try_cancel_operation(enable_attester)
+                .err()
+                .unwrap(),
+            UnknownOperation.into()
+        );
+
+        // a queued action is executed by calling it again once the delay has passed
+        env.set_caller(owner);
+        message_transmitter.enable_attester(attester);
+        env.advance_block_time(1_000);
+        message_transmitter.enable_attester(attester);
+        assert!(message_transmitter.is_attester(attester));
+        assert_eq!(message_transmitter.get_operation_eta(enable_attester), None);
+
+        // an action not executed within the grace period after its eta is queued again
+        let disable_attester = operation_id("disable_attester", &attester).unwrap();
+        message_transmitter.disable_attester(attester);
+        env.advance_block_time(1_000 + GRACE_PERIOD + 1);
+        let now: u64 = env.block_time();
+        message_transmitter.disable_attester(attester);
+        assert!(message_transmitter.is_attester(attester));
+        assert_eq!(
+            message_transmitter.get_operation_eta(disable_attester),
+            Some(now + 1_000)
+        );
+        env.advance_block_time(1_000);
+        message_transmitter.disable_attester(attester);
+        assert!(!message_transmitter.is_attester(attester));
+
+        // the guardian pauses without holding the pauser role
+        message_transmitter.set_guardian(Some(owner));
+        assert_eq!(message_transmitter.get_guardian(), Some(guardian));
+        env.set_caller(guardian);
+        assert!(!message_transmitter.is_pauser(guardian));
+        message_transmitter.pause();
+        assert!(message_transmitter.is_paused());
+        assert_eq!(
+            token_messenger_minter.try_pause().err().unwrap(),
+            crate::security::errors::Error::NotPauser.into()
+        );
+        env.set_caller(owner);
+        token_messenger_minter.set_guardian(Some(guardian));
+        env.set_caller(guardian);
+        token_messenger_minter.pause();
+        assert!(token_messenger_minter.is_paused());
+    }
+    #[test]
+    fn test_compromised_owner_is_timelocked() {
+        use crate::security::errors::Error::{NotPauser, OperationNotReady};
+        use crate::security::timelock::operation_id;
+        let CctpContracts {
+            env,
+            stablecoin,
+            mut message_transmitter,
+            mut token_messenger_minter,
+            owner,
+            ..
+        } = setup_cctp_contracts_with_remote_route(0);
+        let guardian = env.get_account(5);
+        let pauser = env.get_account(6);
+        message_transmitter.set_pauser(pauser, true);
+        token_messenger_minter.set_pauser(pauser, true);
+        let attester: [u8; 20] = [7u8; 20];
+        message_transmitter.enable_attester(attester);
+        message_transmitter.set_guardian(Some(guardian));
+        token_messenger_minter.set_guardian(Some(guardian));
+        message_transmitter.set_timelock_delay(1_000);
+        token_messenger_minter.set_timelock_delay(1_000);
+        env.set_caller(pauser);
+        message_transmitter.pause();
+        token_messenger_minter.pause();
+
+        // the stolen owner key can only queue actions, none of them takes effect right away
+        env.set_caller(owner);
+        let attacker = env.get_account(7);
+        message_transmitter.set_pauser(owner, true);
+        token_messenger_minter.set_pauser(owner, true);
+        assert!(!message_transmitter.is_pauser(owner));
+        assert!(!token_messenger_minter.is_pauser(owner));
+        assert_eq!(
+            message_transmitter.try_unpause().err().unwrap(),
+            NotPauser.into()
+        );
+        assert_eq!(
+            token_messenger_minter.try_unpause().err().unwrap(),
+            NotPauser.into()
+        );
+        message_transmitter.transfer_ownership(attacker);
+        token_messenger_minter.transfer_ownership(attacker);
+        assert_eq!(message_transmitter.pending_owner(), None);
+        assert_eq!(token_messenger_minter.pending_owner(), None);
+        message_transmitter.renounce_ownership();
+        assert_eq!(message_transmitter.owner(), Some(owner));
+        message_transmitter.add_remote_domain(9);
+        message_transmitter.remove_remote_domain(0);
+        assert!(!message_transmitter.is_remote_domain(9));
+        assert!(message_transmitter.is_remote_domain(0));
+        message_transmitter.set_max_message_body_size(U256::zero());
+        message_transmitter.disable_attester(attester);
+        message_transmitter.cancel_attester_rotation();
+        assert_eq!(
+            message_transmitter
+                .try_set_max_message_body_size(U256::zero())
+                .err()
+                .unwrap(),
+            OperationNotReady.into()
+        );
+        assert!(message_transmitter.is_attester(attester));
+        token_messenger_minter.unlink_token_pair([10u8; 32], 0);
+        token_messenger_minter.set_max_burn_amount_per_message(U256::MAX);
+        assert_eq!(
+            token_messenger_minter
+                .try_set_max_burn_amount_per_message(U256::MAX)
+                .err()
+                .unwrap(),
+            OperationNotReady.into()
+        );
+
+        // the guardian cancels the queued actions before their delay has passed
+        env.set_caller(guardian);
+        let message_transmitter_operations = [
+            operation_id("set_pauser", &(owner, true)).unwrap(),
+            operation_id("transfer_ownership", &attacker).unwrap(),
+            operation_id("renounce_ownership", &()).unwrap(),
+            operation_id("add_remote_domain", &9u32).unwrap(),
+            operation_id("remove_remote_domain", &0u32).unwrap(),
+            operation_id("set_max_message_body_size", &U256::zero()).unwrap(),
+            operation_id("disable_attester", &attester).unwrap(),
+            operation_id("cancel_attester_rotation", &()).unwrap(),
+        ];
+        for operation in message_transmitter_operations {
+            message_transmitter.cancel_operation(operation);
+        }
+        let token_messenger_minter_operations = [
+            operation_id("set_pauser", &(owner, true)).unwrap(),
+            operation_id("transfer_ownership", &attacker).unwrap(),
+            operation_id("unlink_token_pair", &([10u8; 32], 0u32)).unwrap(),
+            operation_id("set_max_burn_amount_per_message", &U256::MAX).unwrap(),
+        ];
+        for operation in token_messenger_minter_operations {
+            token_messenger_minter.cancel_operation(operation);
+        }
+
+        // calling again after the delay queues the canceled actions anew
+        env.advance_block_time(1_000);
+        env.set_caller(owner);
+        message_transmitter.set_pauser(owner, true);
+        token_messenger_minter.unlink_token_pair([10u8; 32], 0);
+        assert!(!message_transmitter.is_pauser(owner));
+        assert!(message_transmitter.is_paused());
+        assert!(token_messenger_minter.is_paused());
+
+        // the guardian cannot undo the stop, a pauser resumes once the owner key is secured
+        env.set_caller(guardian);
+        assert_eq!(
+            message_transmitter.try_unpause().err().unwrap(),
+            NotPauser.into()
+        );
+        assert_eq!(
+            token_messenger_minter.try_unpause().err().unwrap(),
+            NotPauser.into()
+        );
+        env.set_caller(pauser);
+        message_transmitter.unpause();
+        token_messenger_minter.unpause();
+        assert!(!message_transmitter.is_paused());
+        assert!(!token_messenger_minter.is_paused());
+        let mint_recipient = env.get_account(8);
+        let message = format_remote_message(&token_messenger_minter, mint_recipient, [0u8; 32], 0);
+        message_transmitter.receive_message(Bytes::from(message), Bytes::from(vec![]));
+        assert_eq!(stablecoin.balance_of(&mint_recipient), 10.into());
+    }
 }
@@ -0,0 +1,432 @@
+//! Emulates a contract package upgrade in the test environment. A contract is installed
+//! with the code of a legacy fixture, which writes the storage of a previous version, and
+//! is later switched to the code of the current version, which keeps that storage.
+use std::cell::{Cell, RefCell};
+
+use odra::casper_types::bytesrepr::Bytes;
+use odra::contract_def::HasIdent;
+use odra::entry_point_callback::EntryPointsCaller;
+use odra::host::{EntryPointsCallerProvider, HostEnv, HostRef};
+use odra::{Address, CallDef, ContractEnv, OdraContract, OdraResult};
+
+// entry point under which the test package exposes the `init` of the new version
+const UPGRADE_INIT: &str = "upgrade_init";
+
+thread_local! {
+    static HOST_ENV: RefCell<Option<HostEnv>> = const { RefCell::new(None) };
+    // contracts that run the code of the current version
+    static UPGRADED: RefCell<Vec<Address>> = const { RefCell::new(Vec::new()) };
+    // set while a legacy contract is installed, which always runs the legacy code
+    static INSTALLING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs a contract running the code of the legacy fixture `L`, which can be upgraded
+/// to the code of `C` later on.
+pub fn deploy_legacy<L: OdraContract, C: OdraContract>(
+    env: &HostEnv,
+    init_args: L::InitArgs,
+) -> Address {
+    HOST_ENV.with(|host_env| *host_env.borrow_mut() = Some(env.clone()));
+    // the package exposes the entry points of both versions, the code decides which exist
+    let mut entry_points = L::HostRef::entry_points_caller(env).entry_points().to_vec();
+    // the test VM rejects calls to `init` after the installation, the `init` of the new
+    // version is exposed under another name
+    entry_points.extend(
+        C::HostRef::entry_points_caller(env)
+            .entry_points()
+            .iter()
+            .cloned()
+            .map(|mut entry_point| {
+                if entry_point.name == "init" {
+                    entry_point.name = UPGRADE_INIT.to_string();
+                }
+                entry_point
+            }),
+    );
+    let caller = EntryPointsCaller::new(env.clone(), entry_points, call::<L::HostRef, C::HostRef>);
+    INSTALLING.with(|installing| installing.set(true));
+    let address = env.new_contract(&L::HostRef::ident(), init_args.into(), caller);
+    INSTALLING.with(|installing| installing.set(false));
+    address.unwrap()
+}
+
+/// Switches the contract to the code of `C`, as adding a new version to its package would.
+pub fn upgrade<C: OdraContract>(env: &HostEnv, address: Address) -> C::HostRef {
+    UPGRADED.with(|upgraded| upgraded.borrow_mut().push(address));
+    C::HostRef::new(address, env.clone())
+}
+
+/// Switches the contract to the code of `C` and calls its `init`, as an upgrade that
+/// initializes the new version would. On a live network only the package upgrader can
+/// call `init`, the test environment does not restrict the caller.
+pub fn upgrade_with_init<C: OdraContract>(
+    env: &HostEnv,
+    address: Address,
+    init_args: C::InitArgs,
+) -> OdraResult<C::HostRef> {
+    UPGRADED.with(|upgraded| upgraded.borrow_mut().push(address));
+    env.call_contract::<()>(address, CallDef::new(UPGRADE_INIT, true, init_args.into()))?;
+    Ok(C::HostRef::new(address, env.clone()))
+}
+
+fn call<L: EntryPointsCallerProvider, C: EntryPointsCallerProvider>(
+    contract_env: ContractEnv,
+    call_def: CallDef,
+) -> OdraResult<Bytes> {
+    let env = HOST_ENV.with(|host_env| host_env.borrow().clone().unwrap());
+    let address = contract_env.self_address();
+    if INSTALLING.with(|installing| installing.get()) {
+        // a fresh environment may install a legacy contract at an address upgraded before
+        UPGRADED.with(|upgraded| upgraded.borrow_mut().retain(|other| *other != address));
+        L::entry_points_caller(&env).call(call_def)
+    } else if call_def.entry_point() == UPGRADE_INIT {
+        let init = CallDef::new("init", true, call_def.args().clone());
+        C::entry_points_caller(&env).call(init)
+    } else if UPGRADED.with(|upgraded| upgraded.borrow().contains(&address)) {
+        C::entry_points_caller(&env).call(call_def)
+    } else {
+        L::entry_points_caller(&env).call(call_def)
+    }
+}
+
+mod migration_tests {
+    use odra::casper_types::bytesrepr::Bytes;
+    use odra::casper_types::U256;
+    use odra::{Address, Mapping, SubModule, Var};
+
+    use super::{deploy_legacy, upgrade};
+    use crate::message_transmitter::errors::Error as MessageTransmitterError;
+    use crate::message_transmitter::storage::Nonces;
+    use crate::message_transmitter::{self, MessageTransmitter};
+    use crate::security::errors::Error as SecurityError;
+    use crate::token_messenger_minter::errors::Error as TokenMessengerMinterError;
+    use crate::token_messenger_minter::storage::RemoteTokenMessengers;
+    use crate::token_messenger_minter::{self, TokenMessengerMinter};
+    use crate::{EthAddress, GenericAddress};
+
+    /// Attesters of the transmitter before they were listed, weighted and rotated.
+    #[odra::module]
+    pub struct LegacyAttesters {
+        attesters: Mapping<EthAddress, bool>,
+    }
+
+    /// Storage layout of the transmitter before ownership and pausing moved into the
+    /// shared submodules, `storage_version` is only written from version 1 on.
+    #[odra::module]
+    #[allow(dead_code)]
+    pub struct LegacyMessageTransmitter {
+        local_domain: Var<u32>,
+        version: Var<u32>,
+        paused: Var<bool>,
+        max_message_body_size: Var<U256>,
+        next_available_nonce: Var<u64>,
+        used_nonces: SubModule<Nonces>,
+        attesters: SubModule<LegacyAttesters>,
+        signature_threshold: Var<u32>,
+        owner: Var<Address>,
+        pending_owner: Var<Option<Address>>,
+        storage_version: Var<u32>,
+    }
+
+    #[odra::module]
+    impl LegacyMessageTransmitter {
+        pub fn init(
+            &mut self,
+            owner: Address,
+            pending_owner: Option<Address>,
+            paused: bool,
+            storage_version: u32,
+            attesters: Vec<EthAddress>,
+        ) {
+            self.local_domain.set(31);
+            for attester in attesters {
+                self.attesters.attesters.set(&attester, true);
+            }
+            self.version.set(2);
+            self.paused.set(paused);
+            self.max_message_body_size.set(1_000_000.into());
+            self.next_available_nonce.set(0);
+            self.signature_threshold.set(1);
+            self.owner.set(owner);
+            self.pending_owner.set(pending_owner);
+            if storage_version > 0 {
+                self.storage_version.set(storage_version);
+            }
+        }
+    }
+
+    /// Storage layout of the token messenger minter before ownership and pausing moved
+    /// into the shared submodules, `storage_version` is only written from version 1 on.
+    #[odra::module]
+    #[allow(dead_code)]
+    pub struct LegacyTokenMessengerMinter {
+        version: Var<u32>,
+        paused: Var<bool>,
+        local_message_transmitter: Var<Address>,
+        remote_token_messengers: SubModule<RemoteTokenMessengers>,
+        max_burn_amount_per_message: Var<U256>,
+        owner: Var<Address>,
+        pending_owner: Var<Option<Address>>,
+        linked_token_pairs: Mapping<(u32, GenericAddress), Option<Address>>,
+        storage_version: Var<u32>,
+    }
+
+    #[odra::module]
+    impl LegacyTokenMessengerMinter {
+        pub fn init(
+            &mut self,
+            owner: Address,
+            pending_owner: Option<Address>,
+            paused: bool,
+            storage_version: u32,
+        ) {
+            self.version.set(2);
+            self.paused.set(paused);
+            self.local_message_transmitter.set(owner);
+            self.max_burn_amount_per_message.set(100.into());
+            self.owner.set(owner);
+            self.pending_owner.set(pending_owner);
+            if storage_version > 0 {
+                self.storage_version.set(storage_version);
+            }
+        }
+    }
+
+    #[test]
+    fn should_migrate_message_transmitter_storage() {
+        for legacy_version in [0, 1] {
+            let env = odra_test::env();
+            let owner = env.get_account(0);
+            let address = deploy_legacy::<LegacyMessageTransmitter, MessageTransmitter>(
+                &env,
+                LegacyMessageTransmitterInitArgs {
+                    owner,
+                    pending_owner: None,
+                    paused: false,
+                    storage_version: legacy_version,
+                    attesters: vec![],
+                },
+            );
+            let mut message_transmitter = upgrade::<MessageTransmitter>(&env, address);
+            assert_eq!(message_transmitter.storage_version(), legacy_version);
+
+            // before the migration the owner is only known to the legacy storage
+            env.set_caller(env.get_account(1));
+            assert_eq!(
+                message_transmitter
+                    .try_migrate(vec![], vec![], vec![], vec![])
+                    .err()
+                    .unwrap(),
+                SecurityError::NotOwner.into()
+            );
+            env.set_caller(owner);
+            message_transmitter.migrate(vec![], vec![], vec![], vec![]);
+            assert_eq!(
+                message_transmitter.storage_version(),
+                message_transmitter::STORAGE_VERSION
+            );
+            assert_eq!(message_transmitter.owner(), Some(owner));
+            assert_eq!(
+                message_transmitter
+                    .try_migrate(vec![], vec![], vec![], vec![])
+                    .err()
+                    .unwrap(),
+                MessageTransmitterError::StorageUpToDate.into()
+            );
+        }
+    }
+
+    #[test]
+    fn should_migrate_token_messenger_minter_storage() {
+        for legacy_version in [0, 1] {
+            let env = odra_test::env();
+            let owner = env.get_account(0);
+            let address = deploy_legacy::<LegacyTokenMessengerMinter, TokenMessengerMinter>(
+                &env,
+                LegacyTokenMessengerMinterInitArgs {
+                    owner,
+                    pending_owner: None,
+                    paused: false,
+                    storage_version: legacy_version,
+                },
+            );
+            let mut token_messenger_minter = upgrade::<TokenMessengerMinter>(&env, address);
+            assert_eq!(token_messenger_minter.storage_version(), legacy_version);
+
+            // before the migration the owner is only known to the legacy storage
+            env.set_caller(env.get_account(1));
+            assert_eq!(
+                token_messenger_minter.try_migrate().err().unwrap(),
+                SecurityError::NotOwner.into()
+            );
+            env.set_caller(owner);
+            token_messenger_minter.migrate();
+            assert_eq!(
+                token_messenger_minter.storage_version(),
+                token_messenger_minter::STORAGE_VERSION
+            );
+            assert_eq!(token_messenger_minter.owner(), Some(owner));
+            assert_eq!(
+                token_messenger_minter.try_migrate().err().unwrap(),
+                TokenMessengerMinterError::StorageUpToDate.into()
+            );
+        }
+    }
+
+    #[test]
+    fn should_move_legacy_ownership_and_pause_state() {
+        let env = odra_test::env();
+        let owner = env.get_account(0);
+        let pending_owner = env.get_account(1);
+        let message_transmitter = deploy_legacy::<LegacyMessageTransmitter, MessageTransmitter>(
+            &env,
+            LegacyMessageTransmitterInitArgs {
+                owner,
+                pending_owner: Some(pending_owner),
+                paused: true,
+                storage_version: 1,
+                attesters: vec![],
+            },
+        );
+        let token_messenger_minter =
+            deploy_legacy::<LegacyTokenMessengerMinter, TokenMessengerMinter>(
+                &env,
+                LegacyTokenMessengerMinterInitArgs {
+                    owner,
+                    pending_owner: Some(pending_owner),
+                    paused: true,
+                    storage_version: 1,
+                },
+            );
+        let mut message_transmitter = upgrade::<MessageTransmitter>(&env, message_transmitter);
+        let mut token_messenger_minter =
+            upgrade::<TokenMessengerMinter>(&env, token_messenger_minter);
+        message_transmitter.migrate(vec![], vec![], vec![], vec![]);
+        token_messenger_minter.migrate();
+
+        // the owner moves into Ownable2Step and becomes a pauser, the pending transfer is dropped
+        assert_eq!(message_transmitter.owner(), Some(owner));
+        assert_eq!(message_transmitter.pending_owner(), None);
+        assert!(message_transmitter.is_pauser(owner));
+        assert_eq!(token_messenger_minter.owner(), Some(owner));
+        assert_eq!(token_messenger_minter.pending_owner(), None);
+        assert!(token_messenger_minter.is_pauser(owner));
+        env.set_caller(pending_owner);
+        assert_eq!(
+            message_transmitter.try_accept_ownership().err().unwrap(),
+            SecurityError::NoPendingOwner.into()
+        );
+        assert_eq!(
+            token_messenger_minter.try_accept_ownership().err().unwrap(),
+            SecurityError::NoPendingOwner.into()
+        );
+
+        // the contracts stay paused until the owner unpauses them
+        assert!(message_transmitter.is_paused());
+        assert!(token_messenger_minter.is_paused());
+        env.set_caller(owner);
+        message_transmitter.unpause();
+        token_messenger_minter.unpause();
+        assert!(!message_transmitter.is_paused());
+        assert!(!token_messenger_minter.is_paused());
+    }
+
+    #[test]
+    fn should_seed_allowlists() {
+        let env = odra_test::env();
+        let owner = env.get_account(0);
+        let address = deploy_legacy::<LegacyMessageTransmitter, MessageTransmitter>(
+            &env,
+            LegacyMessageTransmitterInitArgs {
+                owner,
+                pending_owner: None,
+                paused: false,
+                storage_version: 1,
+                attesters: vec![],
+            },
+        );
+        let token_messenger_minter =
+            deploy_legacy::<LegacyTokenMessengerMinter, TokenMessengerMinter>(
+                &env,
+                LegacyTokenMessengerMinterInitArgs {
+                    owner: address,
+                    pending_owner: None,
+                    paused: false,
+                    storage_version: 1,
+                },
+            );
+        let mut message_transmitter = upgrade::<MessageTransmitter>(&env, address);
+
+        // neither the local domain nor an account can be allowlisted
+        assert_eq!(
+            message_transmitter
+                .try_migrate(vec![0, 31], vec![token_messenger_minter], vec![], vec![])
+                .err()
+                .unwrap(),
+            MessageTransmitterError::InvalidDestinationDomain.into()
+        );
+        assert_eq!(
+            message_transmitter
+                .try_migrate(vec![0], vec![owner], vec![], vec![])
+                .err()
+                .unwrap(),
+            MessageTransmitterError::InvalidMessageRecipient.into()
+        );
+        message_transmitter.migrate(vec![0, 5, 5], vec![token_messenger_minter], vec![], vec![]);
+        assert!(env.emitted(&address, "RemoteDomainAdded"));
+        assert!(env.emitted(&address, "MessageRecipientChanged"));
+        assert!(message_transmitter.is_remote_domain(0));
+        assert!(message_transmitter.is_remote_domain(5));
+        assert!(!message_transmitter.is_remote_domain(6));
+        assert!(message_transmitter.is_message_recipient(token_messenger_minter));
+
+        // routes that were not seeded are rejected
+        message_transmitter.send_message(5, [1u8; 32], Bytes::from(vec![1u8]));
+        assert_eq!(
+            message_transmitter
+                .try_send_message(6, [1u8; 32], Bytes::from(vec![1u8]))
+                .err()
+                .unwrap(),
+            MessageTransmitterError::UnknownRemoteDomain.into()
+        );
+    }
+
+    #[test]
+    fn should_list_legacy_attesters() {
+        let env = odra_test::env();
+        let owner = env.get_account(0);
+        let attester: EthAddress = [1u8; 20];
+        let other_attester: EthAddress = [2u8; 20];
+        let address = deploy_legacy::<LegacyMessageTransmitter, MessageTransmitter>(
+            &env,
+            LegacyMessageTransmitterInitArgs {
+                owner,
+                pending_owner: None,
+                paused: false,
+                storage_version: 1,
+                attesters: vec![attester, other_attester],
+            },
+        );
+        let mut message_transmitter = upgrade::<MessageTransmitter>(&env, address);
+
+        // the legacy mapping keeps accepting the attesters, but cannot list them
+        assert!(message_transmitter.is_attester(attester));
+        assert!(message_transmitter.get_attesters().is_empty());
+
+        // only enabled attesters can be listed
+        assert_eq!(
+            message_transmitter
+                .try_migrate(vec![], vec![], vec![attester, [3u8; 20]], vec![])
+                .err()
+                .unwrap(),
+            MessageTransmitterError::UnknownAttester.into()
+        );
+        message_transmitter.migrate(vec![], vec![], vec![attester, other_attester], vec![]);
+        assert_eq!(
+            message_transmitter.get_attesters(),
+            vec![attester, other_attester]
+        );
+        message_transmitter.disable_attester(other_attester);
+        assert_eq!(message_transmitter.get_attesters(), vec![attester]);
+    }
+}
@@ -3,6 +3,7 @@ use events::DepositForBurn;
 use events::MintAndWithdraw;
 use events::RemoteTokenMessengerAdded;
 use events::RemoteTokenMessengerRemoved;
+use events::StorageMigrated;
 use events::TokenPairLinked;
 use events::TokenPairUnlinked;
 use odra::casper_types::bytesrepr::Bytes;
@@ -26,20 +27,33 @@ pub mod events;
 pub mod storage;
 
 use crate::message_transmitter::MessageTransmitterContractRef;
+use crate::security::errors::Error as SecurityError;
+use crate::security::timelock::operation_id;
+use crate::security::{Ownable2Step, Pausable, Timelock};
 use crate::stablecoin::StablecoinContractRef;
 use errors::Error;
 use storage::RemoteTokenMessengers;
 
+/// Version of the storage layout written by this build of the contract.
+pub const STORAGE_VERSION: u32 = 2;
+
 #[odra::module]
 pub struct TokenMessengerMinter {
     version: Var<u32>,
-    paused: Var<bool>,
+    // superseded by `pausable` in storage version 2
+    legacy_paused: Var<bool>,
     local_message_transmitter: Var<Address>,
     remote_token_messengers: SubModule<RemoteTokenMessengers>,
     max_burn_amount_per_message: Var<U256>,
-    owner: Var<Address>,
-    pending_owner: Var<Option<Address>>,
+    // superseded by `ownable` in storage version 2
+    legacy_owner: Var<Address>,
+    legacy_pending_owner: Var<Option<Address>>,
     linked_token_pairs: Mapping<(u32, GenericAddress), Option<Address>>,
+    storage_version: Var<u32>,
+    ownable: SubModule<Ownable2Step>,
+    pausable: SubModule<Pausable>,
+    // delay of sensitive owner actions and the guardian that can cancel them
+    timelock: SubModule<Timelock>,
 }
 
 #[odra::module]
@@ -51,15 +65,18 @@ impl TokenMessengerMinter {
         local_message_transmitter: Address,
         max_burn_amount_per_message: U256,
         owner: Address,
+        pauser_list: Vec<Address>,
     ) {
         self.version.set(version);
-        self.paused.set(false);
         self.local_message_transmitter
             .set(local_message_transmitter);
         self.max_burn_amount_per_message
             .set(max_burn_amount_per_message);
-        self.owner.set(owner);
-        self.pending_owner.set(None);
+        self.ownable.init(owner);
+        for pauser in pauser_list {
+            self.pausable.set_pauser(&pauser, true);
+        }
+        self.storage_version.set(STORAGE_VERSION);
     }
 
     pub fn deposit_for_burn(
@@ -114,7 +131,7 @@ impl TokenMessengerMinter {
         let version: u32 = original_burn_msg.version();
         let new_burn_message_body: Vec<u8> =
             BurnMessage::format_message(version, &burn_token, &new_mint_recipient, amount, &sender);
-        let local_message_transmitter: MessageTransmitterContractRef =
+        let mut local_message_transmitter: MessageTransmitterContractRef =
             MessageTransmitterContractRef::new(
                 self.env(),
                 self.local_message_transmitter.get().unwrap(),
@@ -144,6 +161,8 @@ impl TokenMessengerMinter {
         message_body: Bytes,
     ) {
         self.require_local_message_transmitter();
+        self.pausable
+            .require_source_domain_not_paused(remote_domain);
         // remote sender must be remote token messenger
         assert_eq!(
             self.remote_token_messengers
@@ -167,23 +186,40 @@ impl TokenMessengerMinter {
         });
     }
     pub fn transfer_ownership(&mut self, new_pending_owner: Address) {
-        self.require_owner();
-        self.pending_owner.set(Some(new_pending_owner));
+        if !self.owner_operation_ready(operation_id("transfer_ownership", &new_pending_owner)) {
+            return;
+        }
+        self.ownable.transfer_ownership(&new_pending_owner);
     }
     pub fn accept_ownership(&mut self) {
-        let pending_owner = self.pending_owner.get().unwrap().unwrap();
-        if self.env().caller() != pending_owner {
-            self.env().revert(Error::InsufficientRights)
+        self.ownable.accept_ownership();
+    }
+    pub fn cancel_ownership_transfer(&mut self) {
+        self.ownable.cancel_ownership_transfer();
+    }
+    pub fn renounce_ownership(&mut self) {
+        if !self.owner_operation_ready(operation_id("renounce_ownership", &())) {
+            return;
         }
-        self.owner.set(pending_owner);
-        self.pending_owner.set(None);
+        self.ownable.renounce_ownership();
+    }
+    pub fn owner(&self) -> Option<Address> {
+        self.ownable.get_owner()
+    }
+    pub fn pending_owner(&self) -> Option<Address> {
+        self.ownable.get_pending_owner()
     }
     pub fn add_remote_token_messenger(
         &mut self,
         domain: u32,
         remote_token_messenger: GenericAddress,
     ) {
-        self.require_owner();
+        if !self.owner_operation_ready(operation_id(
+            "add_remote_token_messenger",
+            &(domain, remote_token_messenger),
+        )) {
+            return;
+        }
         self.remote_token_messengers
             .add_remote_token_messenger(domain, remote_token_messenger);
         self.env().emit_event(RemoteTokenMessengerAdded {
@@ -192,7 +228,9 @@ impl TokenMessengerMinter {
         });
     }
     pub fn remove_remote_token_messenger(&mut self, domain: u32) {
-        self.require_owner();
+        if !self.owner_operation_ready(operation_id("remove_remote_token_messenger", &domain)) {
+            return;
+        }
         let token_messenger: GenericAddress = self
             .remote_token_messengers
             .get_remote_token_messenger(domain)
@@ -211,7 +249,12 @@ impl TokenMessengerMinter {
         remote_token: GenericAddress,
         domain: u32,
     ) {
-        self.require_owner();
+        if !self.owner_operation_ready(operation_id(
+            "link_token_pair",
+            &(local_token, remote_token, domain),
+        )) {
+            return;
+        }
         self.linked_token_pairs
             .set(&(domain, remote_token), Some(local_token));
         self.env().emit_event(TokenPairLinked {
@@ -221,7 +264,9 @@ impl TokenMessengerMinter {
         });
     }
     pub fn unlink_token_pair(&mut self, remote_token: GenericAddress, domain: u32) {
-        self.require_owner();
+        if !self.owner_operation_ready(operation_id("unlink_token_pair", &(remote_token, domain))) {
+            return;
+        }
         let local_token: Address = self
             .linked_token_pairs
             .get(&(domain, remote_token))
@@ -235,15 +280,71 @@ impl TokenMessengerMinter {
         });
     }
     pub fn pause(&mut self) {
-        self.require_owner();
-        self.paused.set(true);
+        // the guardian can stop the contract without holding the pauser role
+        if !self.timelock.is_guardian(&self.env().caller()) {
+            self.require_pauser();
+        }
+        self.pausable.pause();
     }
     pub fn unpause(&mut self) {
-        self.require_owner();
-        self.paused.set(false);
+        // unlike pausing, resuming is left to the pausers so a guardian cannot undo a stop
+        self.require_pauser();
+        self.pausable.unpause();
+    }
+    pub fn is_paused(&self) -> bool {
+        self.pausable.is_paused()
+    }
+    pub fn set_destination_domain_paused(&mut self, domain: u32, paused: bool) {
+        self.require_pauser();
+        self.pausable.set_destination_domain_paused(domain, paused);
+    }
+    pub fn set_source_domain_paused(&mut self, domain: u32, paused: bool) {
+        self.require_pauser();
+        self.pausable.set_source_domain_paused(domain, paused);
+    }
+    pub fn is_destination_domain_paused(&self, domain: u32) -> bool {
+        self.pausable.is_destination_domain_paused(domain)
+    }
+    pub fn is_source_domain_paused(&self, domain: u32) -> bool {
+        self.pausable.is_source_domain_paused(domain)
+    }
+    pub fn set_pauser(&mut self, pauser: Address, enabled: bool) {
+        if !self.owner_operation_ready(operation_id("set_pauser", &(pauser, enabled))) {
+            return;
+        }
+        self.pausable.set_pauser(&pauser, enabled);
+    }
+    pub fn is_pauser(&self, account: Address) -> bool {
+        self.pausable.is_pauser(&account)
+    }
+    pub fn set_guardian(&mut self, guardian: Option<Address>) {
+        if !self.owner_operation_ready(operation_id("set_guardian", &guardian)) {
+            return;
+        }
+        self.timelock.set_guardian(guardian);
+    }
+    pub fn get_guardian(&self) -> Option<Address> {
+        self.timelock.get_guardian()
+    }
+    pub fn set_timelock_delay(&mut self, delay: u64) {
+        if !self.owner_operation_ready(operation_id("set_timelock_delay", &delay)) {
+            return;
+        }
+        self.timelock.set_delay(delay);
+    }
+    pub fn timelock_delay(&self) -> u64 {
+        self.timelock.get_delay()
+    }
+    pub fn get_operation_eta(&self, operation_id: [u8; 32]) -> Option<u64> {
+        self.timelock.get_operation_eta(operation_id)
+    }
+    pub fn cancel_operation(&mut self, operation_id: [u8; 32]) {
+        self.timelock.cancel(operation_id);
     }
     pub fn set_max_burn_amount_per_message(&mut self, amount: U256) {
-        self.require_owner();
+        if !self.owner_operation_ready(operation_id("set_max_burn_amount_per_message", &amount)) {
+            return;
+        }
         self.max_burn_amount_per_message.set(amount);
     }
     // Mint get_local_token(burn_token) on the Casper domain
@@ -271,7 +372,13 @@ impl TokenMessengerMinter {
         self.require_not_paused();
         let mut stable_coin_contract: StablecoinContractRef =
             StablecoinContractRef::new(self.env(), burn_token);
-        stable_coin_contract.burn(burn_amount, self.env().caller());
+        // pull the deposit into our own balance and burn it from there
+        stable_coin_contract.transfer_from(
+            &self.env().caller(),
+            &self.env().self_address(),
+            &burn_amount,
+        );
+        stable_coin_contract.burn_own(burn_amount);
     }
     fn _deposit_for_burn(
         &self,
@@ -281,6 +388,8 @@ impl TokenMessengerMinter {
         burn_token: GenericAddress,
         destination_caller: GenericAddress,
     ) {
+        self.pausable
+            .require_destination_domain_not_paused(destination_domain);
         assert_ne!(burn_amount, 0u64);
         assert_ne!(mint_recipient, [0u8; 32]);
         let token_contract_address: Address = generic_address_to_contract_address(burn_token);
@@ -344,16 +453,69 @@ impl TokenMessengerMinter {
         }
     }
 
-    fn require_not_paused(&self) {
-        if self.paused.get().unwrap() {
-            self.env().revert(Error::ContractIsPaused)
+    pub fn storage_version(&self) -> u32 {
+        self.storage_version.get_or_default()
+    }
+    // Migrates the storage of a previous version to the layout of the installed code,
+    // called by the owner once after a contract package upgrade.
+    pub fn migrate(&mut self) {
+        let from_version = self.storage_version();
+        // before storage version 2 the owner is only known to the legacy storage
+        if from_version < 2 {
+            if self.legacy_owner.get() != Some(self.env().caller()) {
+                self.env().revert(SecurityError::NotOwner)
+            }
+        } else {
+            self.require_owner();
+        }
+        if from_version >= STORAGE_VERSION {
+            self.env().revert(Error::StorageUpToDate)
         }
+        for version in from_version..STORAGE_VERSION {
+            self.migrate_from(version);
+        }
+        self.storage_version.set(STORAGE_VERSION);
+        self.env().emit_event(StorageMigrated {
+            from_version,
+            to_version: STORAGE_VERSION,
+        });
     }
-    fn require_owner(&self) {
-        if self.env().caller() != self.owner.get().unwrap() {
-            self.env().revert(Error::InsufficientRights)
+    fn migrate_from(&mut self, version: u32) {
+        match version {
+            // unversioned installations already use the version 1 layout
+            0 => {}
+            // ownership and the pause flag moved into the shared submodules,
+            // a pending ownership transfer is dropped and has to be started again
+            1 => {
+                if let Some(owner) = self.legacy_owner.get() {
+                    self.ownable.init(owner);
+                    // the owner used to pause the contract
+                    self.pausable.set_pauser(&owner, true);
+                }
+                self.legacy_pending_owner.set(None);
+                if self.legacy_paused.get_or_default() {
+                    self.pausable.pause();
+                }
+            }
+            _ => self.env().revert(Error::UnknownStorageVersion),
         }
     }
+    fn require_not_paused(&self) {
+        self.pausable.require_not_paused();
+    }
+    fn require_owner(&self) {
+        self.ownable.assert_owner(&self.env().caller());
+    }
+    // Requires the owner and returns true once a sensitive action may be executed, actions
+    // are queued for the timelock delay first.
+    fn owner_operation_ready(&mut self, operation_id: Result<[u8; 32], SecurityError>) -> bool {
+        self.require_owner();
+        let operation_id: [u8; 32] = operation_id.unwrap_or_else(|error| self.env().revert(error));
+        self.timelock.schedule_or_execute(operation_id)
+    }
+    fn require_pauser(&self) {
+        self.pausable.assert_pauser(&self.env().caller());
+    }
     fn require_local_message_transmitter(&self) {
         if self.env().caller() != self.local_message_transmitter.get().unwrap() {
             self.env().revert(Error::InsufficientRights)
@@ -3,4 +3,6 @@
 pub enum Error {
     InsufficientRights = 40000,
     ContractIsPaused = 40001,
+    StorageUpToDate = 40002,
+    UnknownStorageVersion = 40003,
 }
@@ -46,3 +46,9 @@ pub struct TokenPairUnlinked {
     pub remote_token: GenericAddress,
     pub domain: u32,
 }
+
+#[odra::event]
+pub struct StorageMigrated {
+    pub from_version: u32,
+    pub to_version: u32,
+}